@@ -0,0 +1,49 @@
+// En este archivo se implementa el comando `diary`, que guarda en un archivo
+// todo lo que se escribe y se muestra en la consola. Excede a la materia.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::Mutex,
+};
+
+/// Archivo donde se está guardando la sesión, si `diary` está activado.
+static DIARY: Mutex<Option<File>> = Mutex::new(None);
+
+/// Imprime en la consola, igual que `println!`, y además lo guarda en el
+/// archivo de `diary` si está activado.
+macro_rules! output {
+    () => {
+        output!("")
+    };
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        println!("{}", text);
+        $crate::diary::write(&text);
+    }};
+}
+
+/// Empieza a guardar la sesión en el archivo `path`. Si el archivo ya existe,
+/// se agrega al final.
+pub fn start(path: &str) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("No se pudo abrir el archivo \"{}\": {}", path, e))?;
+    *DIARY.lock().unwrap() = Some(file);
+    Ok(())
+}
+
+/// Deja de guardar la sesión.
+pub fn stop() {
+    *DIARY.lock().unwrap() = None;
+}
+
+/// Guarda una línea en el archivo de `diary`, si está activado.
+pub fn write(text: &str) {
+    if let Some(file) = DIARY.lock().unwrap().as_mut() {
+        // Si falla la escritura, no se interrumpe la sesión.
+        writeln!(file, "{}", text).ok();
+    }
+}
@@ -0,0 +1,71 @@
+// Aquí se definen los errores que pueden ocurrir al evaluar una expresión.
+// Distinguir el tipo de error permite reaccionar distinto a cada uno, aunque al
+// usuario solo se le muestra el mensaje.
+
+use std::fmt;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum EvalError {
+    /// Las dimensiones de los operandos no son compatibles con la operación.
+    DimensionMismatch(String),
+    /// Se usó una variable que no está definida. Guarda su nombre.
+    UndefinedVariable(String),
+    /// Se llamó a una función que no está definida. Guarda su nombre y, si hay
+    /// alguna parecida, el nombre de esa función.
+    UndefinedFunction {
+        name: String,
+        suggestion: Option<String>,
+    },
+    /// Se llamó a una función con una cantidad incorrecta de argumentos.
+    WrongArguments(String),
+    /// La operación no está definida para el tipo de valor recibido.
+    TypeError(String),
+    /// El valor está fuera del dominio de la operación (como la raíz de un negativo).
+    DomainError(String),
+    /// La matriz es singular, o el sistema no tiene solución única.
+    Singular(String),
+    /// Cualquier otro error, como los que vienen de las operaciones de matrix/mod.rs.
+    Other(String),
+}
+
+impl EvalError {
+    /// Crea un error de dimensiones a partir del mensaje de una operación de matrices.
+    pub fn from_dimensions(message: &str) -> Self {
+        EvalError::DimensionMismatch(message.to_string())
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::UndefinedVariable(name) => {
+                write!(f, "La variable \"{}\" no está definida", name)
+            }
+            EvalError::UndefinedFunction { name, suggestion } => {
+                write!(f, "La función {} no está definida", name)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ". ¿Quiso decir {}?", suggestion)?;
+                }
+                Ok(())
+            }
+            EvalError::DimensionMismatch(message)
+            | EvalError::WrongArguments(message)
+            | EvalError::TypeError(message)
+            | EvalError::DomainError(message)
+            | EvalError::Singular(message)
+            | EvalError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<&str> for EvalError {
+    fn from(message: &str) -> Self {
+        EvalError::Other(message.to_string())
+    }
+}
+
+impl From<String> for EvalError {
+    fn from(message: String) -> Self {
+        EvalError::Other(message)
+    }
+}
@@ -3,11 +3,92 @@
 
 use crate::utils::format_float;
 
+use super::error::EvalError;
 use super::matrix::Matrix;
-use super::utils::nearly_equal;
+use super::random::Rng;
+use super::utils::{gamma, nearly_equal};
 use super::value::Value;
 
-type FnResult = Result<Value, String>;
+type FnResult = Result<Value, EvalError>;
+
+/// Obtiene un número entero no negativo (como una cantidad de filas) a partir de un valor.
+/// `name` se usa en el mensaje de error.
+fn to_count(x: &Value, name: &str) -> Result<usize, EvalError> {
+    match x {
+        Value::Scalar(x) if *x >= 0.0 && x.fract() == 0.0 && x.is_finite() => Ok(*x as usize),
+        _ => Err(EvalError::DomainError(format!(
+            "{} debe ser un número entero no negativo",
+            name
+        ))),
+    }
+}
+
+/// Mayor cantidad de elementos de una matriz creada por una función como `rand`.
+/// Un pedido más grande agotaría la memoria.
+const MAX_ELEMENTS: usize = 10_000_000;
+
+/// Controla que una matriz de MxN a crear no sea demasiado grande. Tampoco se acepta
+/// una dimensión mayor a `MAX_ELEMENTS`, aunque la otra sea 0.
+fn check_size(rows: usize, cols: usize) -> Result<(), EvalError> {
+    match rows.checked_mul(cols) {
+        Some(count) if count <= MAX_ELEMENTS && rows.max(cols) <= MAX_ELEMENTS => Ok(()),
+        _ => Err(EvalError::DomainError(format!(
+            "La matriz es demasiado grande (puede tener hasta {} elementos)",
+            MAX_ELEMENTS
+        ))),
+    }
+}
+
+/// Obtiene un número entero (positivo o negativo) a partir de un valor.
+/// `name` se usa en el mensaje de error.
+fn to_integer(x: &Value, name: &str) -> Result<i64, EvalError> {
+    match x {
+        Value::Scalar(x) if x.fract() == 0.0 && x.is_finite() => Ok(*x as i64),
+        _ => Err(EvalError::DomainError(format!(
+            "{} debe ser un número entero",
+            name
+        ))),
+    }
+}
+
+/// Error para una operación que no está definida para el tipo de `x`. `what` es el
+/// comienzo del mensaje, como "El seno no está definido".
+fn type_error(what: &str, x: &Value) -> EvalError {
+    EvalError::TypeError(format!("{} para {}", what, x.type_plural()))
+}
+
+/// Como `type_error`, para operaciones entre dos valores: menciona el tipo del primero
+/// que no sea un número real ni una matriz.
+fn pair_type_error(what: &str, left: &Value, right: &Value) -> EvalError {
+    match left {
+        Value::Scalar(_) | Value::Matrix(_) => type_error(what, right),
+        _ => type_error(what, left),
+    }
+}
+
+/// Aplica `f` a un número real, o elemento a elemento a una matriz.
+fn map_elements(x: &Value, what: &str, f: impl Fn(f64) -> f64) -> FnResult {
+    match x {
+        Value::Scalar(x) => Ok(Value::Scalar(f(*x))),
+        Value::Matrix(a) => Ok(Value::Matrix(a.map(f))),
+        _ => Err(type_error(what, x)),
+    }
+}
+
+/// Como `map_elements`, pero `f` puede fallar. En una matriz se valida cada elemento
+/// antes de calcular.
+fn try_map_elements(x: &Value, what: &str, f: impl Fn(f64) -> Result<f64, EvalError>) -> FnResult {
+    match x {
+        Value::Scalar(x) => Ok(Value::Scalar(f(*x)?)),
+        Value::Matrix(a) => {
+            for (_, _, val) in a {
+                f(val)?;
+            }
+            Ok(Value::Matrix(a.map(|val| f(val).unwrap())))
+        }
+        _ => Err(type_error(what, x)),
+    }
+}
 
 /// Suma dos valores.
 pub fn add(left: &Value, right: &Value) -> FnResult {
@@ -16,8 +97,14 @@ pub fn add(left: &Value, right: &Value) -> FnResult {
         (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a + b)),
         // Si ambos son matrices, se suman.
         // Ver cómo se implementa la suma de matrices en matrix/mod.rs
-        (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(Matrix::add(a, b)?)),
-        _ => Err("La suma entre matrices y reales no está definida".to_string()),
+        (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(
+            Matrix::add(a, b).map_err(EvalError::from_dimensions)?,
+        )),
+        // Si uno es un número real y el otro una matriz, se suma el número a cada elemento.
+        (Value::Scalar(a), Value::Matrix(b)) | (Value::Matrix(b), Value::Scalar(a)) => {
+            Ok(Value::Matrix(b.map(|val| val + a)))
+        }
+        _ => Err(pair_type_error("La suma no está definida", left, right)),
     }
 }
 
@@ -29,6 +116,7 @@ pub fn negate(x: &Value) -> FnResult {
         // Si es una matriz, se lo escala por -1.
         // Ver cómo se implementa la multiplicación por un escalar en matrix/mod.rs
         Value::Matrix(a) => Ok(Value::Matrix(a.scale(-1.0))),
+        _ => Err(type_error("El opuesto no está definido", x)),
     }
 }
 
@@ -45,11 +133,35 @@ pub fn multiply(left: &Value, right: &Value) -> FnResult {
         (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a * b)),
         // Si ambos son matrices, se multiplican.
         // Ver cómo se implementa la multiplicación de matrices en matrix/mod.rs
-        (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(Matrix::multiply(a, b)?)),
+        (Value::Matrix(a), Value::Matrix(b)) => {
+            let result = Matrix::multiply(a, b).map_err(EvalError::from_dimensions)?;
+            // Si el resultado es de 1x1 (por ejemplo, un vector fila por un vector columna,
+            // que es el producto escalar), se devuelve como número real.
+            if result.is_number() {
+                Ok(Value::Scalar(result.get(0, 0)?))
+            } else {
+                Ok(Value::Matrix(result))
+            }
+        }
         // Si uno es un número real y el otro una matriz, se escala la matriz por el número.
         // Ver cómo se implementa la multiplicación por un escalar en matrix/mod.rs
         (Value::Scalar(a), Value::Matrix(b)) => Ok(Value::Matrix(b.scale(*a))),
         (Value::Matrix(a), Value::Scalar(b)) => Ok(Value::Matrix(a.scale(*b))),
+        _ => Err(pair_type_error(
+            "La multiplicación no está definida",
+            left,
+            right,
+        )),
+    }
+}
+
+/// Calcula el producto de Kronecker de dos valores.
+pub fn kron(left: &Value, right: &Value) -> FnResult {
+    match (left, right) {
+        // Ver cómo se implementa el producto de Kronecker en matrix/mod.rs
+        (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(Matrix::kron(a, b))),
+        // Si alguno es un número real, es lo mismo que multiplicar.
+        _ => multiply(left, right),
     }
 }
 
@@ -58,14 +170,38 @@ pub fn inverse(x: &Value) -> FnResult {
     match x {
         // Si es un número real, se divide 1 entre él.
         Value::Scalar(x) => {
+            // Como en MATLAB, 1/0 es infinito (con el signo del 0), y por ende 0/0
+            // termina siendo nan.
             if nearly_equal(*x, 0.0) {
-                return Err("1/0 no está definido".to_string());
+                return Ok(Value::Scalar(f64::INFINITY.copysign(*x)));
             }
             Ok(Value::Scalar(1.0 / x))
         }
         // Si es una matriz, se calcula su inversa.
         // Ver cómo se implementa la inversa de matrices en matrix/mod.rs
-        Value::Matrix(a) => Ok(Value::Matrix(a.inverse()?)),
+        Value::Matrix(a) => Ok(Value::Matrix(a.inverse().map_err(|e| {
+            if a.is_square() {
+                EvalError::Singular(e)
+            } else {
+                EvalError::DimensionMismatch(e)
+            }
+        })?)),
+        _ => Err(type_error("El inverso no está definido", x)),
+    }
+}
+
+/// Calcula la pseudoinversa de Moore-Penrose de un valor.
+pub fn pinv(x: &Value) -> FnResult {
+    match x {
+        // Para un número real, es su inverso (o 0 si es 0).
+        Value::Scalar(x) => Ok(Value::Scalar(if nearly_equal(*x, 0.0) {
+            0.0
+        } else {
+            1.0 / x
+        })),
+        // Ver cómo se implementa la pseudoinversa en matrix/mod.rs
+        Value::Matrix(a) => Ok(Value::Matrix(a.pseudo_inverse()?)),
+        _ => Err(type_error("La pseudoinversa no está definida", x)),
     }
 }
 
@@ -85,84 +221,441 @@ pub fn right_divide(left: &Value, right: &Value) -> FnResult {
 pub fn pow(a: &Value, n: &Value) -> FnResult {
     if let Value::Scalar(n) = n {
         match a {
-            // Si es un número real, se eleva a la potencia.
-            Value::Scalar(a) => Ok(Value::Scalar(a.powf(*n))),
+            // Si es un número real, se eleva a la potencia. Por convención, 0^0 = 1.
+            Value::Scalar(a) => {
+                // Un número negativo elevado a un exponente no entero da un número complejo.
+                if *a < 0.0 && n.fract() != 0.0 {
+                    return Err(EvalError::DomainError(
+                        "No se puede elevar un número negativo a un exponente no entero (el resultado es complejo). Para raíces reales, use nthroot(x, n)".to_string(),
+                    ));
+                }
+                Ok(Value::Scalar(a.powf(*n)))
+            }
             // Si es una matriz, se eleva a la potencia.
             // Ver cómo se implementa la potencia de matrices en matrix/mod.rs
             Value::Matrix(a) => Ok(Value::Matrix(a.pow(*n)?)),
+            _ => Err(type_error("La potencia no está definida", a)),
         }
+    } else if let Value::Matrix(_) = n {
+        Err(EvalError::TypeError(
+            "El exponente de la potencia no puede ser una matriz".to_string(),
+        ))
     } else {
-        Err("El exponente de la potencia no puede ser una matriz".to_string())
+        Err(type_error("La potencia no está definida", n))
     }
 }
 
+/// Compara dos valores con `op`.
+/// - Si ambos son números reales, devuelve un booleano.
+/// - Si alguno es una matriz, se compara elemento a elemento (un número real se compara
+///   con cada elemento) y devuelve una matriz de 1 (verdadero) y 0 (falso).
+fn compare(left: &Value, right: &Value, op: impl Fn(f64, f64) -> bool) -> FnResult {
+    let to_number = |condition: bool| f64::from(u8::from(condition));
+    match (left, right) {
+        (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Bool(op(*a, *b))),
+        // Ver cómo se implementan las operaciones elemento a elemento en matrix/mod.rs
+        (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(
+            Matrix::zip_with(a, b, |a, b| to_number(op(a, b)))
+                .map_err(EvalError::from_dimensions)?,
+        )),
+        (Value::Scalar(a), Value::Matrix(b)) => Ok(Value::Matrix(b.map(|b| to_number(op(*a, b))))),
+        (Value::Matrix(a), Value::Scalar(b)) => Ok(Value::Matrix(a.map(|a| to_number(op(a, *b))))),
+        _ => Err(pair_type_error(
+            "Las comparaciones no están definidas",
+            left,
+            right,
+        )),
+    }
+}
+
+/// Devuelve si el valor izquierdo es menor que el derecho.
+pub fn less(left: &Value, right: &Value) -> FnResult {
+    compare(left, right, |a, b| a < b && !nearly_equal(a, b))
+}
+
+/// Devuelve si el valor izquierdo es mayor que el derecho.
+pub fn greater(left: &Value, right: &Value) -> FnResult {
+    compare(left, right, |a, b| a > b && !nearly_equal(a, b))
+}
+
+/// Devuelve si el valor izquierdo es menor o igual que el derecho.
+pub fn less_equal(left: &Value, right: &Value) -> FnResult {
+    compare(left, right, |a, b| a < b || nearly_equal(a, b))
+}
+
+/// Devuelve si el valor izquierdo es mayor o igual que el derecho.
+pub fn greater_equal(left: &Value, right: &Value) -> FnResult {
+    compare(left, right, |a, b| a > b || nearly_equal(a, b))
+}
+
+/// Devuelve si ambos valores son iguales (con tolerancia).
+pub fn equal(left: &Value, right: &Value) -> FnResult {
+    compare(left, right, nearly_equal)
+}
+
+/// Devuelve si ambos valores son distintos (con tolerancia).
+pub fn not_equal(left: &Value, right: &Value) -> FnResult {
+    compare(left, right, |a, b| !nearly_equal(a, b))
+}
+
+/// Devuelve si ambos valores tienen la misma dimensión y todos sus elementos son
+/// iguales (con tolerancia). A diferencia de `==`, devuelve un único booleano, y si
+/// las dimensiones no coinciden devuelve falso en vez de un error.
+pub fn isequal(left: &Value, right: &Value) -> FnResult {
+    match (left, right) {
+        (Value::Text(a), Value::Text(b)) => return Ok(Value::Bool(a == b)),
+        (Value::Text(_), _) | (_, Value::Text(_)) => return Ok(Value::Bool(false)),
+        _ => {}
+    }
+    if dims(left) != dims(right) {
+        return Ok(Value::Bool(false));
+    }
+
+    // Un número real y una matriz de 1x1 se consideran iguales si su valor lo es
+    let as_matrix = |x: &Value| match x {
+        Value::Matrix(a) => a.clone(),
+        x => Matrix::from_scalar(x.as_scalar().unwrap_or(f64::NAN)),
+    };
+    let (left, right) = (as_matrix(left), as_matrix(right));
+    for i in 0..left.rows() {
+        for j in 0..left.cols() {
+            if !nearly_equal(left.get(i, j)?, right.get(i, j)?) {
+                return Ok(Value::Bool(false));
+            }
+        }
+    }
+    Ok(Value::Bool(true))
+}
+
+/// Interpreta un valor como verdadero o falso. Los números reales distintos de 0
+/// son verdaderos.
+fn to_bool(x: &Value) -> Result<bool, EvalError> {
+    match x {
+        Value::Bool(b) => Ok(*b),
+        Value::Scalar(x) => Ok(!nearly_equal(*x, 0.0)),
+        _ => Err(EvalError::TypeError(
+            "Los operadores lógicos solo están definidos para números reales y booleanos"
+                .to_string(),
+        )),
+    }
+}
+
+/// Devuelve `a` si la condición es verdadera y `b` si no. Como todos los argumentos
+/// ya están evaluados, no hay cortocircuito: `if(x ~= 0, 1/x, 0)` evalúa 1/x aunque
+/// x sea 0 (acá no es un problema, porque da inf).
+pub fn if_else(condition: &Value, a: &Value, b: &Value) -> FnResult {
+    let condition = to_bool(condition).map_err(|_| {
+        EvalError::TypeError(
+            "La condición de if() debe ser un número real o un booleano".to_string(),
+        )
+    })?;
+    Ok(if condition { a.clone() } else { b.clone() })
+}
+
+/// Calcula la negación lógica de un valor.
+pub fn not(x: &Value) -> FnResult {
+    Ok(Value::Bool(!to_bool(x)?))
+}
+
+/// Calcula el "y" lógico de dos valores.
+/// Ambos ya están evaluados, así que no hay cortocircuito.
+pub fn and(left: &Value, right: &Value) -> FnResult {
+    Ok(Value::Bool(to_bool(left)? && to_bool(right)?))
+}
+
+/// Calcula el "o" lógico de dos valores.
+/// Ambos ya están evaluados, así que no hay cortocircuito.
+pub fn or(left: &Value, right: &Value) -> FnResult {
+    Ok(Value::Bool(to_bool(left)? || to_bool(right)?))
+}
+
 /// Calcula el valor absoluto de un valor.
+/// Si es una matriz, se calcula elemento a elemento.
 pub fn abs(n: &Value) -> FnResult {
-    if let Value::Scalar(n) = n {
-        Ok(Value::Scalar(n.abs()))
-    } else {
-        Err("sqrt() solo puede ser usada con números reales".to_string())
-    }
+    map_elements(n, "El valor absoluto no está definido", f64::abs)
 }
 
 /// Calcula la raíz cuadrada de un valor.
+/// Si es una matriz, se calcula elemento a elemento.
 pub fn sqrt(n: &Value) -> FnResult {
-    if let Value::Scalar(n) = n {
-        if *n < 0.0 {
-            return Err("No se puede calcular la raíz cuadrada de un número negativo".to_string());
+    match n {
+        Value::Scalar(n) => {
+            if *n < 0.0 {
+                return Err(EvalError::DomainError(
+                    "No se puede calcular la raíz cuadrada de un número negativo".to_string(),
+                ));
+            }
+            Ok(Value::Scalar(n.sqrt()))
         }
-        Ok(Value::Scalar(n.sqrt()))
-    } else {
-        Err("sqrt() solo puede ser usada con números reales".to_string())
+        Value::Matrix(a) => {
+            if a.into_iter().any(|(_, _, val)| val < 0.0) {
+                return Err(EvalError::DomainError(
+                    "No se puede calcular la raíz cuadrada de una matriz con elementos negativos"
+                        .to_string(),
+                ));
+            }
+            Ok(Value::Matrix(a.map(f64::sqrt)))
+        }
+        _ => Err(type_error("La raíz cuadrada no está definida", n)),
+    }
+}
+
+/// Calcula la raíz cúbica (real) de un valor. A diferencia de `x^(1/3)`, funciona
+/// con números negativos: cbrt(-8) = -2.
+/// Si es una matriz, se calcula elemento a elemento.
+pub fn cbrt(x: &Value) -> FnResult {
+    map_elements(x, "La raíz cúbica no está definida", f64::cbrt)
+}
+
+/// Calcula la raíz n-ésima real de un número. Si el número es negativo, n debe ser
+/// un entero impar, y el resultado es la raíz negativa: nthroot(-32, 5) = -2.
+fn real_nthroot(x: f64, n: f64) -> Result<f64, EvalError> {
+    if nearly_equal(n, 0.0) {
+        return Err(EvalError::DomainError(
+            "El índice de la raíz no puede ser 0".to_string(),
+        ));
+    }
+    if x >= 0.0 {
+        return Ok(x.powf(1.0 / n));
+    }
+    if n.fract() != 0.0 || n % 2.0 == 0.0 {
+        return Err(EvalError::DomainError(
+            "La raíz de un número negativo solo está definida para índices enteros impares"
+                .to_string(),
+        ));
+    }
+    Ok(-(-x).powf(1.0 / n))
+}
+
+/// Calcula la raíz n-ésima real de un valor.
+/// Si es una matriz, se calcula elemento a elemento.
+pub fn nthroot(x: &Value, n: &Value) -> FnResult {
+    let n = match n.as_scalar() {
+        Some(n) => n,
+        None => {
+            return Err(EvalError::TypeError(
+                "El índice de la raíz debe ser un número real".to_string(),
+            ))
+        }
+    };
+    try_map_elements(x, "La raíz no está definida", |val| real_nthroot(val, n))
+}
+
+/// Restringe un valor al intervalo [lo, hi]: si es menor que lo devuelve lo, y si es
+/// mayor que hi devuelve hi. Si es una matriz, se aplica elemento a elemento.
+pub fn clamp(x: &Value, lo: &Value, hi: &Value) -> FnResult {
+    let (lo, hi) = match (lo.as_scalar(), hi.as_scalar()) {
+        (Some(lo), Some(hi)) => (lo, hi),
+        _ => {
+            return Err(EvalError::TypeError(
+                "Los límites de clamp() deben ser números reales".to_string(),
+            ))
+        }
+    };
+    // f64::clamp falla si lo > hi o si alguno es nan.
+    if lo.is_nan() || hi.is_nan() || lo > hi {
+        return Err(EvalError::DomainError(
+            "El límite inferior de clamp() debe ser menor o igual que el superior".to_string(),
+        ));
+    }
+
+    map_elements(x, "clamp() no está definida", |val| val.clamp(lo, hi))
+}
+
+/// Calcula sqrt(a^2 + b^2) sin desbordar: elevar al cuadrado un número grande da inf
+/// aunque el resultado sí entre en un f64.
+pub fn hypot(a: &Value, b: &Value) -> FnResult {
+    match (a.as_scalar(), b.as_scalar()) {
+        (Some(a), Some(b)) => Ok(Value::Scalar(a.hypot(b))),
+        _ => Err(EvalError::TypeError(
+            "hypot() solo está definida para dos números reales".to_string(),
+        )),
     }
 }
 
 /// Calcula el factorial de un valor.
 pub fn factorial(n: &Value) -> FnResult {
     if let Value::Scalar(n) = n {
+        // NaN e infinito no tienen factorial (y no se pueden convertir a enteros).
+        if !n.is_finite() {
+            return Err(EvalError::DomainError(
+                "El factorial solo está definido para números finitos".to_string(),
+            ));
+        }
         if *n < 0.0 {
-            return Err("No se puede calcular el factorial de un número negativo".to_string());
+            return Err(EvalError::DomainError(
+                "No se puede calcular el factorial de un número negativo".to_string(),
+            ));
         }
-        let n = *n as u64;
-        let mut result = 1_u64;
-        for i in 2..=n {
-            result *= i;
+        // Los números no enteros se extienden con la función gamma: n! = Γ(n + 1).
+        if n.fract() != 0.0 {
+            return Ok(Value::Scalar(gamma(n + 1.0)));
         }
-        Ok(Value::Scalar(result as f64))
+        // A partir de 171! el resultado no entra en un f64, así que es infinito.
+        if *n > 170.0 {
+            return Ok(Value::Scalar(f64::INFINITY));
+        }
+        // Se multiplica en f64 en vez de en enteros para no desbordar a partir de 21!.
+        // Hasta 22! el resultado es exacto.
+        let mut result = 1.0;
+        for i in 2..=(*n as u32) {
+            result *= f64::from(i);
+        }
+        Ok(Value::Scalar(result))
     } else {
-        Err("El factorial no está definido para matrices".to_string())
+        Err(type_error("El factorial no está definido", n))
+    }
+}
+
+/// Calcula el número combinatorio "n en k": la cantidad de formas de elegir k
+/// elementos de un conjunto de n.
+pub fn nchoosek(n: &Value, k: &Value) -> FnResult {
+    let n = to_count(n, "n")?;
+    let k = to_count(k, "k")?;
+    if k > n {
+        return Err(EvalError::DomainError(
+            "k no puede ser mayor que n en nchoosek(n, k)".to_string(),
+        ));
+    }
+    // A partir de 2^53, no todos los enteros se pueden representar con un f64, así que
+    // el resultado ya no sería exacto.
+    if n > 1 << 53 {
+        return Err(EvalError::DomainError(
+            "n no puede ser mayor que 2^53 en nchoosek(n, k)".to_string(),
+        ));
+    }
+
+    // Como C(n, k) = C(n, n - k), se usa el menor de los dos para hacer menos pasos.
+    let k = k.min(n - k);
+    // En vez de calcular n!/(k!(n-k)!), que desborda enseguida, se multiplica y
+    // divide de a un factor: después del paso i, el resultado es C(n - k + i, i),
+    // que siempre es entero.
+    let mut result = 1.0;
+    for i in 1..=k {
+        result = result * (n - k + i) as f64 / i as f64;
+        if result.is_infinite() {
+            // Ya desbordó y no va a bajar, así que no tiene sentido seguir.
+            break;
+        }
     }
+    Ok(Value::Scalar(result.round()))
 }
 
 /// Calcula el seno de un valor.
 pub fn sin(x: &Value) -> FnResult {
-    match x {
-        Value::Scalar(x) => Ok(Value::Scalar(x.sin())),
-        Value::Matrix(_) => Err("El seno no está definido para matrices".to_string()),
-    }
+    // Si es una matriz, se calcula elemento a elemento.
+    map_elements(x, "El seno no está definido", f64::sin)
 }
 
 /// Calcula el coseno de un valor.
 pub fn cos(x: &Value) -> FnResult {
-    match x {
-        Value::Scalar(x) => Ok(Value::Scalar(x.cos())),
-        Value::Matrix(_) => Err("El coseno no está definido para matrices".to_string()),
+    // Si es una matriz, se calcula elemento a elemento.
+    map_elements(x, "El coseno no está definido", f64::cos)
+}
+
+/// Calcula la tangente de un número. En los múltiplos impares de π/2 no está definida:
+/// `f64::tan` devuelve un número enorme (por el redondeo de π), así que se devuelve un
+/// error en su lugar.
+fn real_tan(x: f64) -> Result<f64, EvalError> {
+    let k = (x / std::f64::consts::FRAC_PI_2).round();
+    if k % 2.0 != 0.0 && nearly_equal(x, k * std::f64::consts::FRAC_PI_2) {
+        return Err(EvalError::DomainError(
+            "La tangente no está definida en los múltiplos impares de pi/2".to_string(),
+        ));
     }
+    Ok(x.tan())
 }
 
 /// Calcula la tangente de un valor.
 pub fn tan(x: &Value) -> FnResult {
-    match x {
-        Value::Scalar(x) => Ok(Value::Scalar(x.tan())),
-        Value::Matrix(_) => Err("La tangente no está definido para matrices".to_string()),
+    // Si es una matriz, se calcula elemento a elemento.
+    try_map_elements(x, "La tangente no está definida", real_tan)
+}
+
+/// Calcula el seno de un ángulo en grados. Los múltiplos de 90° se calculan en forma
+/// exacta, ya que al pasar a radianes se pierde precisión (sin(π) no da exactamente 0).
+fn sin_degrees(x: f64) -> f64 {
+    let x = x.rem_euclid(360.0);
+    if x == 0.0 || x == 180.0 {
+        0.0
+    } else if x == 90.0 {
+        1.0
+    } else if x == 270.0 {
+        -1.0
+    } else {
+        x.to_radians().sin()
+    }
+}
+
+/// Calcula el coseno de un ángulo en grados, como cos(x) = sin(x + 90°).
+fn cos_degrees(x: f64) -> f64 {
+    sin_degrees(x + 90.0)
+}
+
+/// Calcula la tangente de un ángulo en grados. No está definida en 90° + k180°.
+fn tan_degrees(x: f64) -> Result<f64, EvalError> {
+    let x = x.rem_euclid(180.0);
+    if x == 90.0 {
+        Err(EvalError::DomainError(
+            "La tangente no está definida en 90° más los múltiplos de 180°".to_string(),
+        ))
+    } else if x == 0.0 {
+        Ok(0.0)
+    } else if x == 45.0 {
+        Ok(1.0)
+    } else if x == 135.0 {
+        Ok(-1.0)
+    } else {
+        Ok(x.to_radians().tan())
     }
 }
 
+/// Calcula el seno de un valor en grados.
+pub fn sind(x: &Value) -> FnResult {
+    // Si es una matriz, se calcula elemento a elemento.
+    map_elements(x, "El seno no está definido", sin_degrees)
+}
+
+/// Calcula el coseno de un valor en grados.
+pub fn cosd(x: &Value) -> FnResult {
+    // Si es una matriz, se calcula elemento a elemento.
+    map_elements(x, "El coseno no está definido", cos_degrees)
+}
+
+/// Calcula la tangente de un valor en grados.
+pub fn tand(x: &Value) -> FnResult {
+    // Si es una matriz, se calcula elemento a elemento.
+    try_map_elements(x, "La tangente no está definida", tan_degrees)
+}
+
+/// Convierte un ángulo de grados a radianes.
+/// Si es una matriz, se convierte elemento a elemento.
+pub fn deg2rad(x: &Value) -> FnResult {
+    map_elements(x, "deg2rad() no está definida", f64::to_radians)
+}
+
+/// Convierte un ángulo de radianes a grados.
+/// Si es una matriz, se convierte elemento a elemento.
+pub fn rad2deg(x: &Value) -> FnResult {
+    map_elements(x, "rad2deg() no está definida", f64::to_degrees)
+}
+
 /// Calcula el logarítmo natural de un valor.
 pub fn log(x: &Value) -> FnResult {
     match x {
         Value::Scalar(x) => Ok(Value::Scalar(x.ln())),
-        Value::Matrix(_) => Err("El logarítmo no está definido para matrices".to_string()),
+        _ => Err(type_error("El logarítmo no está definido", x)),
+    }
+}
+
+/// Calcula la exponencial de un valor. Si es una matriz, es la exponencial matricial
+/// (no elemento a elemento).
+pub fn expm(x: &Value) -> FnResult {
+    match x {
+        Value::Scalar(x) => Ok(Value::Scalar(x.exp())),
+        // Ver cómo se implementa la exponencial de matrices en matrix/mod.rs
+        Value::Matrix(a) => Ok(Value::Matrix(a.expm()?)),
+        _ => Err(type_error("La exponencial no está definida", x)),
     }
 }
 
@@ -172,7 +665,7 @@ pub fn transpose(a: &Value) -> FnResult {
         // Ver cómo se implementa la traspuesta de una matriz en matrix/mod.rs
         Ok(Value::Matrix(a.transpose()))
     } else {
-        Err("La traspuesta no está definida para números reales".to_string())
+        Err(type_error("La traspuesta no está definida", a))
     }
 }
 
@@ -180,129 +673,518 @@ pub fn transpose(a: &Value) -> FnResult {
 pub fn det(a: &Value) -> FnResult {
     if let Value::Matrix(a) = a {
         // Ver cómo se implementa el determinante de una matriz en matrix/mod.rs
-        Ok(Value::Scalar(a.determinant()?))
+        Ok(Value::Scalar(
+            a.determinant().map_err(EvalError::from_dimensions)?,
+        ))
+    } else {
+        Err(type_error("El determinante no está definido", a))
+    }
+}
+
+/// Cambia la dimensión de una matriz a MxN. Los elementos se toman fila por fila,
+/// por lo que `reshape([1, 2, 3; 4, 5, 6], 3, 2)` es `[1, 2; 3, 4; 5, 6]`.
+pub fn reshape(a: &Value, m: &Value, n: &Value) -> FnResult {
+    let m = to_count(m, "La cantidad de filas")?;
+    let n = to_count(n, "La cantidad de columnas")?;
+    match a {
+        Value::Matrix(a) => Ok(Value::Matrix(a.reshape(m, n)?)),
+        // Un número real es una matriz de 1x1
+        Value::Scalar(x) => Ok(Value::Matrix(Matrix::from_scalar(*x).reshape(m, n)?)),
+        _ => Err(type_error("reshape() no está definida", a)),
+    }
+}
+
+/// Repite un valor en una grilla de MxN copias.
+pub fn repmat(a: &Value, m: &Value, n: &Value) -> FnResult {
+    let m = to_count(m, "La cantidad de filas")?;
+    let n = to_count(n, "La cantidad de columnas")?;
+    match a {
+        Value::Matrix(a) => {
+            // Si alguna dimensión desborda, queda en usize::MAX y check_size la rechaza.
+            check_size(a.rows().saturating_mul(m), a.cols().saturating_mul(n))?;
+            Ok(Value::Matrix(a.repmat(m, n)))
+        }
+        // Un número real es una matriz de 1x1, así que se obtiene una matriz constante
+        Value::Scalar(x) => {
+            check_size(m, n)?;
+            Ok(Value::Matrix(Matrix::from_scalar(*x).repmat(m, n)))
+        }
+        _ => Err(type_error("repmat() no está definida", a)),
+    }
+}
+
+/// Invierte el orden de las columnas de un valor.
+pub fn fliplr(a: &Value) -> FnResult {
+    match a {
+        Value::Matrix(a) => Ok(Value::Matrix(a.fliplr())),
+        // Un número real es una matriz de 1x1, así que queda igual
+        Value::Scalar(_) => Ok(a.clone()),
+        _ => Err(type_error("fliplr() no está definida", a)),
+    }
+}
+
+/// Invierte el orden de las filas de un valor.
+pub fn flipud(a: &Value) -> FnResult {
+    match a {
+        Value::Matrix(a) => Ok(Value::Matrix(a.flipud())),
+        // Un número real es una matriz de 1x1, así que queda igual
+        Value::Scalar(_) => Ok(a.clone()),
+        _ => Err(type_error("flipud() no está definida", a)),
+    }
+}
+
+/// Rota un valor 90 grados en sentido antihorario `k` veces.
+pub fn rot90(a: &Value, k: &Value) -> FnResult {
+    let k = to_integer(k, "La cantidad de rotaciones")?;
+    match a {
+        Value::Matrix(a) => Ok(Value::Matrix(a.rot90(k))),
+        // Un número real es una matriz de 1x1, así que queda igual
+        Value::Scalar(_) => Ok(a.clone()),
+        _ => Err(type_error("rot90() no está definida", a)),
+    }
+}
+
+/// Devuelve la parte triangular inferior de un valor, desde la diagonal `k`.
+pub fn tril(a: &Value, k: &Value) -> FnResult {
+    let k = to_integer(k, "La diagonal")?;
+    match a {
+        Value::Matrix(a) => Ok(Value::Matrix(a.tril(k))),
+        // Un número real es una matriz de 1x1
+        Value::Scalar(x) => Ok(Value::Scalar(Matrix::from_scalar(*x).tril(k).get(0, 0)?)),
+        _ => Err(type_error("tril() no está definida", a)),
+    }
+}
+
+/// Devuelve la parte triangular superior de un valor, desde la diagonal `k`.
+pub fn triu(a: &Value, k: &Value) -> FnResult {
+    let k = to_integer(k, "La diagonal")?;
+    match a {
+        Value::Matrix(a) => Ok(Value::Matrix(a.triu(k))),
+        // Un número real es una matriz de 1x1
+        Value::Scalar(x) => Ok(Value::Scalar(Matrix::from_scalar(*x).triu(k).get(0, 0)?)),
+        _ => Err(type_error("triu() no está definida", a)),
+    }
+}
+
+/// Devuelve la cantidad de filas y columnas de un valor.
+fn dims(a: &Value) -> (usize, usize) {
+    match a {
+        Value::Scalar(_) => (1, 1),
+        Value::Matrix(a) => (a.rows(), a.cols()),
+        // Un texto se considera un vector fila de caracteres
+        Value::Text(t) => (1, t.chars().count()),
+        Value::Bool(_) | Value::Function(_) => (1, 1),
+    }
+}
+
+/// Devuelve las dimensiones de un valor como un vector fila [filas, columnas].
+pub fn size(a: &Value) -> FnResult {
+    let (rows, cols) = dims(a);
+    Ok(Value::Matrix(Matrix::from_row_slice(
+        1,
+        2,
+        &[rows as f64, cols as f64],
+    )?))
+}
+
+/// Devuelve la cantidad total de elementos de un valor.
+pub fn numel(a: &Value) -> FnResult {
+    let (rows, cols) = dims(a);
+    Ok(Value::Scalar((rows * cols) as f64))
+}
+
+/// Devuelve la mayor de las dimensiones de un valor, o 0 si no tiene elementos.
+pub fn length(a: &Value) -> FnResult {
+    let (rows, cols) = dims(a);
+    if rows * cols == 0 {
+        return Ok(Value::Scalar(0.0));
+    }
+    Ok(Value::Scalar(rows.max(cols) as f64))
+}
+
+/// Devuelve si el valor no tiene elementos.
+pub fn isempty(a: &Value) -> FnResult {
+    let (rows, cols) = dims(a);
+    Ok(Value::Bool(rows * cols == 0))
+}
+
+/// Devuelve si el valor es un número real o una matriz de 1x1.
+pub fn isscalar(a: &Value) -> FnResult {
+    Ok(Value::Bool(dims(a) == (1, 1)))
+}
+
+/// Devuelve si el valor es de 1xN o de Nx1 (con N >= 1).
+pub fn isvector(a: &Value) -> FnResult {
+    let (rows, cols) = dims(a);
+    Ok(Value::Bool(rows * cols > 0 && (rows == 1 || cols == 1)))
+}
+
+/// Devuelve si el valor es una matriz cuadrada igual a su transpuesta (con
+/// tolerancia). Un número real siempre es simétrico.
+pub fn issymmetric(a: &Value) -> FnResult {
+    match a {
+        Value::Matrix(a) => Ok(Value::Bool(a.is_symmetric())),
+        Value::Scalar(_) => Ok(Value::Bool(true)),
+        _ => Ok(Value::Bool(false)),
+    }
+}
+
+/// Devuelve el nombre del tipo de un valor, con los nombres de MATLAB: "double" para
+/// números reales y matrices, "char" para textos, "logical" para booleanos y
+/// "function_handle" para funciones.
+pub fn class(x: &Value) -> FnResult {
+    let name = match x {
+        Value::Scalar(_) | Value::Matrix(_) => "double",
+        Value::Text(_) => "char",
+        Value::Bool(_) => "logical",
+        Value::Function(_) => "function_handle",
+    };
+    Ok(Value::Text(name.to_string()))
+}
+
+/// Muestra un valor sin el "x = " adelante y lo devuelve sin cambios.
+pub fn disp(x: &Value) -> FnResult {
+    // Las matrices se muestran en un bloque separado por líneas vacías, que acá sobran.
+    output!("{}", x.to_string().trim_matches('\n'));
+    Ok(x.clone())
+}
+
+/// Convierte un número en texto, con el mismo formato con el que se muestra.
+pub fn num2str(x: &Value) -> FnResult {
+    match x {
+        Value::Scalar(x) => Ok(Value::Text(format_float(*x))),
+        Value::Text(_) => Ok(x.clone()),
+        Value::Matrix(_) => Err(EvalError::TypeError(
+            "num2str() solo está definida para números. Para matrices, use mat2str()".to_string(),
+        )),
+        _ => Err(type_error("num2str() no está definida", x)),
+    }
+}
+
+/// Convierte una matriz en un texto que, escrito como expresión, vuelve a dar la
+/// misma matriz, como "[1, 2; 3, 4]". Los elementos se escriben con todos sus
+/// dígitos, así no se pierde precisión.
+pub fn mat2str(a: &Value) -> FnResult {
+    // A diferencia de format_float, no se redondea.
+    let exact = |val: f64| {
+        if val.is_nan() {
+            "nan".to_string()
+        } else if val.is_infinite() {
+            if val > 0.0 { "inf" } else { "-inf" }.to_string()
+        } else if val == 0.0 {
+            // Previene el caso "-0"
+            "0".to_string()
+        } else {
+            format!("{}", val)
+        }
+    };
+
+    match a {
+        Value::Scalar(x) => Ok(Value::Text(exact(*x))),
+        Value::Matrix(a) => {
+            let rows: Vec<String> = (0..a.rows())
+                .map(|i| {
+                    (0..a.cols())
+                        .map(|j| exact(a.get(i, j).unwrap()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect();
+            Ok(Value::Text(format!("[{}]", rows.join("; "))))
+        }
+        _ => Err(type_error("mat2str() no está definida", a)),
+    }
+}
+
+/// Genera una matriz de MxN con números aleatorios uniformes en [0, 1).
+pub fn rand(rng: &mut Rng, m: &Value, n: &Value) -> FnResult {
+    let m = to_count(m, "La cantidad de filas")?;
+    let n = to_count(n, "La cantidad de columnas")?;
+    check_size(m, n)?;
+    let mut result = Matrix::new(m, n);
+    for i in 0..m {
+        for j in 0..n {
+            result.set(i, j, rng.next_f64())?;
+        }
+    }
+    Ok(Value::Matrix(result))
+}
+
+/// Normas que se pueden pedir con `norm(A, p)`.
+enum NormKind {
+    One,
+    Two,
+    Inf,
+}
+
+/// Calcula la norma de un valor.
+/// - Para un vector (1xN o Nx1), la norma euclídea, o la norma `p` si se indica (1, 2 o inf).
+/// - Para una matriz, la norma de Frobenius, o la norma inducida `p` si se indica (1, 2 o inf).
+/// - Para un número real, su valor absoluto.
+pub fn norm(a: &Value, p: Option<&Value>) -> FnResult {
+    let kind = match p {
+        None => None,
+        Some(Value::Scalar(p)) if *p == 1.0 => Some(NormKind::One),
+        Some(Value::Scalar(p)) if *p == 2.0 => Some(NormKind::Two),
+        Some(Value::Scalar(p)) if *p == f64::INFINITY => Some(NormKind::Inf),
+        Some(_) => {
+            return Err(EvalError::DomainError(
+                "El segundo argumento de norm() debe ser 1, 2 o inf".to_string(),
+            ))
+        }
+    };
+
+    match a {
+        Value::Scalar(x) => Ok(Value::Scalar(x.abs())),
+        // La norma de una matriz vacía es 0.
+        Value::Matrix(a) if a.rows() == 0 || a.cols() == 0 => Ok(Value::Scalar(0.0)),
+        // Vectores
+        Value::Matrix(a) if a.rows() == 1 || a.cols() == 1 => {
+            let values = a.into_iter().map(|(_, _, val)| val.abs());
+            let norm = match kind {
+                Some(NormKind::One) => values.sum(),
+                Some(NormKind::Inf) => values.fold(0.0, f64::max),
+                // Norma euclídea, que coincide con la de Frobenius
+                Some(NormKind::Two) | None => a.frobenius_norm(),
+            };
+            Ok(Value::Scalar(norm))
+        }
+        // Matrices
+        Value::Matrix(a) => {
+            let norm = match kind {
+                None => a.frobenius_norm(),
+                Some(NormKind::One) => a.norm_1(),
+                Some(NormKind::Inf) => a.norm_inf(),
+                // La norma 2 es la raíz del mayor autovalor de AᵀA (que es simétrica).
+                Some(NormKind::Two) => {
+                    let ata = Matrix::multiply(&a.transpose(), a)?;
+                    let eigenvalues = ata.eigenvalues_symmetric()?;
+                    eigenvalues.get(eigenvalues.rows() - 1, 0)?.sqrt()
+                }
+            };
+            Ok(Value::Scalar(norm))
+        }
+        _ => Err(type_error("La norma no está definida", a)),
+    }
+}
+
+/// Calcula el número de condición de una matriz, cond(A) = ||A|| * ||A^(-1)||,
+/// usando la norma 2. Si la matriz es singular, es infinito.
+pub fn cond(a: &Value) -> FnResult {
+    let two = Value::Scalar(2.0);
+    match a {
+        Value::Matrix(m) => {
+            if !m.is_square() {
+                return Err(EvalError::DimensionMismatch(
+                    "El número de condición solo está definido para matrices cuadradas".to_string(),
+                ));
+            }
+            // Como en MATLAB, el número de condición de una matriz vacía es 0.
+            if m.rows() == 0 {
+                return Ok(Value::Scalar(0.0));
+            }
+            match m.inverse() {
+                Ok(inverse) => multiply(
+                    &norm(a, Some(&two))?,
+                    &norm(&Value::Matrix(inverse), Some(&two))?,
+                ),
+                // Si no tiene inversa, la matriz es singular.
+                Err(_) => Ok(Value::Scalar(f64::INFINITY)),
+            }
+        }
+        Value::Scalar(x) => Ok(Value::Scalar(if nearly_equal(*x, 0.0) {
+            f64::INFINITY
+        } else {
+            1.0
+        })),
+        _ => Err(type_error("El número de condición no está definido", a)),
+    }
+}
+
+/// Calcula los autovalores de una matriz simétrica.
+pub fn eig(a: &Value) -> FnResult {
+    if let Value::Matrix(a) = a {
+        // Por ahora solo se soportan matrices simétricas, que tienen autovalores reales.
+        if !a.is_symmetric() {
+            return Err(EvalError::DomainError(
+                "Los autovalores solo están implementados para matrices simétricas".to_string(),
+            ));
+        }
+        // Ver cómo se implementa el cálculo de los autovalores en matrix/mod.rs
+        Ok(Value::Matrix(a.eigenvalues_symmetric()?))
     } else {
-        Err("La traspuesta no está definida para números reales".to_string())
+        Err(type_error("Los autovalores no están definidos", a))
     }
 }
 
+/// Calcula el menor (i, j) de una matriz: el determinante de la submatriz que se
+/// obtiene al eliminar la fila i y la columna j. Los índices empiezan en 1.
+pub fn minor(a: &Value, i: &Value, j: &Value) -> FnResult {
+    if let Value::Matrix(a) = a {
+        if !a.is_square() {
+            return Err(EvalError::DimensionMismatch(
+                "El menor solo está definido para matrices cuadradas".to_string(),
+            ));
+        }
+        let i = to_count(i, "La fila")?;
+        let j = to_count(j, "La columna")?;
+        if i == 0 || j == 0 || i > a.rows() || j > a.cols() {
+            return Err(EvalError::DomainError(format!(
+                "Los índices deben estar entre 1 y {} (la matriz es de {}x{})",
+                a.rows(),
+                a.rows(),
+                a.cols()
+            )));
+        }
+        // Ver cómo se obtiene la submatriz en matrix/mod.rs
+        let submatrix = a.submatrix_excluding(i - 1, j - 1)?;
+        Ok(Value::Scalar(submatrix.determinant()?))
+    } else {
+        Err(type_error("El menor no está definido", a))
+    }
+}
+
+/// Calcula la matriz adjunta (traspuesta de la matriz de cofactores).
+pub fn adj(a: &Value) -> FnResult {
+    if let Value::Matrix(a) = a {
+        // Ver cómo se implementa la adjunta en matrix/mod.rs
+        Ok(Value::Matrix(a.adjugate()?))
+    } else {
+        Err(type_error("La adjunta no está definida", a))
+    }
+}
+
+/// Calcula la factorización LU de una matriz (PA = LU).
+/// Como solo se puede devolver un valor, se muestran P y L, y se devuelve U.
+pub fn lu(a: &Value) -> FnResult {
+    if let Value::Matrix(a) = a {
+        // Ver cómo se implementa la factorización LU en matrix/mod.rs
+        let (p, l, u) = a.lu()?;
+        output!("P = {}", p);
+        output!("L = {}", l);
+        Ok(Value::Matrix(u))
+    } else {
+        Err(type_error("La factorización LU no está definida", a))
+    }
+}
+
+/// Calcula la solución por cuadrados mínimos de un sistema Ax = b incompatible, es decir,
+/// el x que minimiza ||Ax - b||. Se obtiene resolviendo las ecuaciones normales AᵀAx = Aᵀb.
+fn least_squares(a: &Matrix, b: &Matrix) -> FnResult {
+    let transpose = a.transpose();
+    let ata = Matrix::multiply(&transpose, a)?;
+    let atb = Matrix::multiply(&transpose, b)?;
+    let inverse = ata.inverse().map_err(|_| {
+        EvalError::Singular(
+            "El sistema es incompatible y A no tiene rango columna completo, por lo que no se puede ajustar por cuadrados mínimos"
+                .to_string(),
+        )
+    })?;
+
+    output!(
+        "El sistema es incompatible. Se devuelve la solución por cuadrados mínimos (AᵀAx = Aᵀb)"
+    );
+    Ok(Value::Matrix(Matrix::multiply(&inverse, &atb)?))
+}
+
 /// Resuelve un sistema de ecuaciones lineales de la forma Ax = b.
 /// A: matriz de coeficientes
 /// b: vector columna de términos independientes
 ///
 /// Se resuelve obteniendo la forma escalonada reducida de Gauss-Jordan.
+/// Si el sistema es incompatible y tiene más ecuaciones que incógnitas, se devuelve
+/// la solución por cuadrados mínimos.
 pub fn linsolve(a: &Value, b: &Value) -> FnResult {
+    solve_system(a, b, false)
+}
+
+/// Igual que `linsolve`, pero antes de clasificar el sistema muestra la forma
+/// escalonada reducida de la matriz aumentada (A|b).
+pub fn linsolve_verbose(a: &Value, b: &Value) -> FnResult {
+    solve_system(a, b, true)
+}
+
+/// Resuelve el sistema Ax = b. Si `verbose` es verdadero, muestra la forma
+/// escalonada reducida de la matriz aumentada.
+fn solve_system(a: &Value, b: &Value, verbose: bool) -> FnResult {
     if let Value::Matrix(a) = a {
         if let Value::Matrix(b) = b {
             if a.cols() == 0 {
-                return Err("La matriz A no puede ser vacía".to_string());
+                return Err(EvalError::DimensionMismatch(
+                    "La matriz A no puede ser vacía".to_string(),
+                ));
             }
 
-            if a.rows() != b.rows() {
-                return Err("La cantidad de filas de A y b no coincide".to_string());
-            }
+            // Si b es un vector fila (1xN) con tantos elementos como filas tiene A,
+            // se lo traspone para que sea un vector columna (Nx1).
+            let transposed;
+            let b = if b.rows() == 1 && b.cols() == a.rows() && a.rows() != 1 {
+                output!("Nota: b es un vector fila, se usará su traspuesta b'");
+                transposed = b.transpose();
+                &transposed
+            } else {
+                b
+            };
 
             if b.cols() != 1 {
-                return Err("La matriz b debe tener una sola columna".to_string());
+                return Err(EvalError::DimensionMismatch(format!(
+                    "La matriz b debe ser un vector columna de {}x1 (o un vector fila de 1x{}), pero es de {}x{}",
+                    a.rows(),
+                    a.rows(),
+                    b.rows(),
+                    b.cols()
+                )));
             }
 
-            let inverse = a.inverse();
-            if let Ok(inverse) = inverse {
-                // Si existe la inversa de A, A no es singular y, por ende,
-                // el sistema es compatible determinado. x = A^(-1)b
-
-                println!("El sistema es compatible determinado");
-                return Ok(Value::Matrix(Matrix::multiply(&inverse, b)?));
+            if a.rows() != b.rows() {
+                return Err(EvalError::DimensionMismatch(format!(
+                    "La cantidad de filas de A ({}) y b ({}) no coincide",
+                    a.rows(),
+                    b.rows()
+                )));
             }
 
-            let rows = a.rows();
-            let cols = a.cols();
             // Creo la matriz aumentada (A|b)
-            let mut matrix = Matrix::new(rows, cols + 1);
+            let mut augmented = Matrix::new(a.rows(), a.cols() + 1);
 
             // Copio los valores de A en la matriz
             for (row, col, val) in a {
-                matrix.set(row, col, val)?;
+                augmented.set(row, col, val)?;
             }
 
             // Copio los valores de b en la matriz
-            for i in 0..rows {
-                matrix.set(i, cols, b.get(i, 0)?)?
+            for i in 0..a.rows() {
+                augmented.set(i, a.cols(), b.get(i, 0)?)?
             }
 
-            // Recorro la diagonal con un i y un j.
-            // El i es el índice de la fila y el j el de la columna.
-            //
-            // La estrategia es buscar la primera fila tal que Akj != 0 e intercambiarla con la fila i.
-            // Si no existe tal fila, se avanza a la columna siguiente y se repite el proceso.
-            //
-            // Una vez encontrado el pivote, se permuta y se divide cada elemento de la fila i por Aij.
-            // Así, Aij = 1. Luego, se resta a cada fila k != i la fila i multiplicada por Akj. Así, los elementos
-            // de la columna j quedan en 0 para esas filas.
-            //
-            // Todo esto para que quede la matriz en forma escalonada reducida de Gauss-Jordan, la cual
-            // será analizada más adelante.
-
-            let mut i: usize = 0;
-            let mut j: usize = 0;
-            while i < rows && j < cols {
-                // Obtengo el elemento de la diagonal (Aij, que será el pivote)
-                let mut pivot = matrix.get(i, j).unwrap();
-                if nearly_equal(pivot, 0.0) {
-                    // Busco la primera fila tal que Akj != 0
-                    let mut found = false;
-                    // Solo busco en las filas i+1 a rows-1, ya que las filas anteriores ya están en 0
-                    let mut k = i + 1;
-                    while !found && k < rows {
-                        pivot = matrix.get(k, j).unwrap();
-                        if nearly_equal(pivot, 0.0) {
-                            k += 1;
-                        } else {
-                            found = true;
-                        }
-                    }
-                    if !found {
-                        // No encontré ningún elemento no nulo en la columna j
-                        // Por lo tanto, paso a la siguiente columna
-                        j += 1;
-                        continue;
-                    } else {
-                        // Permuto la fila k con la fila i
-                        matrix.swap_rows(k, i)?;
-                    }
-                }
-
-                // Divido la fila i por Aij, así Aij = 1
-                let factor = 1.0 / pivot;
-                matrix.scale_row(i, factor)?;
-
-                // Ahora, toca restar a cada fila k != i la fila i multiplicada por Akj.
-                for k in 0..rows {
-                    if k != i {
-                        // factor = -Akj
-                        let factor = -matrix.get(k, j)?;
+            if verbose {
+                output!("rref(A|b) = {}", augmented.rref()?);
+            }
 
-                        // Sumo a cada elemento de la fila k la fila i multiplicada por el factor,
-                        // así los elementos de la columna k quedan en 0.
-                        matrix.add_row(k, i, factor)?;
-                    }
-                }
+            let inverse = a.inverse();
+            if let Ok(inverse) = inverse {
+                // Si existe la inversa de A, A no es singular y, por ende,
+                // el sistema es compatible determinado. x = A^(-1)b
 
-                // Avanzo en diagonal
-                i += 1;
-                j += 1;
+                output!("El sistema es compatible determinado");
+                return Ok(Value::Matrix(Matrix::multiply(&inverse, b)?));
             }
 
-            // Ahora, la matriz está en forma escalonada reducida de Gauss-Jordan.
+            // Obtengo la forma escalonada reducida de Gauss-Jordan de la matriz aumentada
+            // (A|b), la cual será analizada más adelante.
+            // Ver cómo se calcula en matrix/mod.rs
+            let matrix = augmented.rref()?;
+            let rows = a.rows();
+            let cols = a.cols();
 
             // Por cómo se construyó la matriz, si existen filas nulas, estas serán las últimas.
             // Así, empezando desde la última fila, compruebo que todas las filas nulas sean de la forma
             // 0 ... 0 | b con b != 0. Si esto no se cumple, el sistema es incompatible.
 
-            i = rows - 1;
+            let mut i = rows - 1;
             while i > 0 {
                 let mut row_all_ceros = true;
-                j = 0;
+                let mut j = 0;
                 while row_all_ceros && j < cols {
                     if !nearly_equal(matrix.get(i, j)?, 0.0) {
                         row_all_ceros = false;
@@ -318,10 +1200,17 @@ pub fn linsolve(a: &Value, b: &Value) -> FnResult {
 
                 // La fila es nula, por lo que compruebo que b != 0
 
-                let b = matrix.get(i, cols)?;
-                if !nearly_equal(b, 0.0) {
+                let bi = matrix.get(i, cols)?;
+                if !nearly_equal(bi, 0.0) {
                     // La fila es nula y b != 0, por lo que el sistema es incompatible.
-                    return Err("El sistema es incompatible".to_string());
+                    if rows > cols {
+                        // Si hay más ecuaciones que incógnitas (sistema sobredeterminado),
+                        // se busca la solución por cuadrados mínimos.
+                        return least_squares(a, b);
+                    }
+                    return Err(EvalError::Singular(
+                        "El sistema es incompatible".to_string(),
+                    ));
                 }
 
                 i -= 1;
@@ -347,8 +1236,8 @@ pub fn linsolve(a: &Value, b: &Value) -> FnResult {
                     solution.set(i, 0, matrix.get(i, cols)?)?;
                 }
 
-                println!("El sistema es compatible determinado");
-                return Ok(Value::Matrix(solution));
+                output!("El sistema es compatible determinado");
+                Ok(Value::Matrix(solution))
             } else {
                 // El sistema es indeterminado
 
@@ -388,7 +1277,7 @@ pub fn linsolve(a: &Value, b: &Value) -> FnResult {
 
                             // Como el despeje que se hace es
                             // x + y = b => x = b - y, el signo se invierte
-                            let sign = if x > 0.0 { " - " } else { " + " };
+                            let sign = if x > 0.0 { "-" } else { "+" };
                             let factor = format_float(x.abs());
                             var.push_str(&format!(" {sign} {factor}*x{n}", n = (j + 1)));
                         }
@@ -399,25 +1288,685 @@ pub fn linsolve(a: &Value, b: &Value) -> FnResult {
                     i += 1;
                 }
 
-                println!("El sistema es compatible indeterminado. El conjunto solución es:\n");
-
-                // Imprimo el conjunto solución
-                for var in &vars {
-                    println!("{}", var);
-                }
-
-                println!(
-                    "\nEl sistema tiene {} variables dependientes y {} variables independientes\n",
+                output!(
+                    "El sistema es compatible indeterminado. Tiene {} variables dependientes y {} variables independientes\n",
                     vars.len(),
                     cols - vars.len(),
                 );
 
-                return Err("El sistema no tiene una única solución".to_string());
+                // Devuelvo el conjunto solución como texto, para poder guardarlo
+                Ok(Value::Text(vars.join("\n")))
             }
         } else {
-            Err("b debe ser una matriz.".to_string())
+            Err(EvalError::TypeError("b debe ser una matriz.".to_string()))
         }
     } else {
-        Err("A debe ser una matriz".to_string())
+        Err(EvalError::TypeError("A debe ser una matriz".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// Crea un valor matriz a partir de sus elementos, fila por fila.
+    fn matrix(rows: usize, cols: usize, data: &[f64]) -> Value {
+        Value::Matrix(Matrix::from_row_slice(rows, cols, data).unwrap())
+    }
+
+    /// Obtiene el número que devolvió una función, o falla si no devolvió un número.
+    fn scalar(result: FnResult) -> f64 {
+        match result {
+            Ok(Value::Scalar(x)) => x,
+            Ok(_) => panic!("se esperaba un número"),
+            Err(error) => panic!("error inesperado: {}", error),
+        }
+    }
+
+    /// Obtiene el booleano que devolvió una función, o falla si no devolvió uno.
+    fn boolean(result: FnResult) -> bool {
+        match result {
+            Ok(Value::Bool(b)) => b,
+            Ok(_) => panic!("se esperaba un booleano"),
+            Err(error) => panic!("error inesperado: {}", error),
+        }
+    }
+
+    /// Obtiene la dimensión y los elementos (fila por fila) de la matriz que devolvió
+    /// una función, o falla si no devolvió una matriz.
+    fn elements(result: FnResult) -> (usize, usize, Vec<f64>) {
+        match result {
+            Ok(Value::Matrix(m)) => (
+                m.rows(),
+                m.cols(),
+                (&m).into_iter().map(|(_, _, x)| x).collect(),
+            ),
+            Ok(_) => panic!("se esperaba una matriz"),
+            Err(error) => panic!("error inesperado: {}", error),
+        }
+    }
+
+    /// Comprueba que una función haya devuelto una matriz de rows x cols con los
+    /// elementos `expected` (fila por fila), salvo errores de redondeo.
+    fn assert_matrix(result: FnResult, rows: usize, cols: usize, expected: &[f64]) {
+        let (result_rows, result_cols, data) = elements(result);
+        assert_eq!((result_rows, result_cols), (rows, cols));
+        for (x, y) in data.iter().zip(expected) {
+            assert!((x - y).abs() < 1e-9, "{:?} != {:?}", data, expected);
+        }
+    }
+
+    /// Obtiene el mensaje de error que devolvió una función.
+    fn error_message(result: FnResult) -> String {
+        match result {
+            Ok(_) => panic!("se esperaba un error"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn type_errors_name_the_received_type() {
+        let text = Value::Text("a".to_string());
+        assert_eq!(
+            error_message(factorial(&text)),
+            "El factorial no está definido para textos"
+        );
+        assert_eq!(
+            error_message(factorial(&matrix(1, 2, &[1.0, 2.0]))),
+            "El factorial no está definido para matrices"
+        );
+        assert_eq!(
+            error_message(add(&Value::Scalar(1.0), &text)),
+            "La suma no está definida para textos"
+        );
+        assert_eq!(
+            error_message(sin(&text)),
+            "El seno no está definido para textos"
+        );
+    }
+
+    #[test]
+    fn row_times_column_is_a_scalar() {
+        let row = matrix(1, 3, &[1.0, 2.0, 3.0]);
+        let column = matrix(3, 1, &[1.0, 2.0, 3.0]);
+        assert_eq!(scalar(multiply(&row, &column)), 14.0);
+        assert!(matches!(multiply(&column, &row), Ok(Value::Matrix(_))));
+    }
+
+    #[test]
+    fn length_of_empty_matrix_is_zero() {
+        assert_eq!(scalar(length(&Value::Matrix(Matrix::new(0, 3)))), 0.0);
+        assert_eq!(scalar(length(&Value::Matrix(Matrix::new(3, 0)))), 0.0);
+        assert_eq!(scalar(length(&matrix(2, 3, &[0.0; 6]))), 3.0);
+        assert_eq!(scalar(length(&Value::Scalar(5.0))), 1.0);
+    }
+
+    #[test]
+    fn norm_of_empty_matrix_is_zero() {
+        let two = Value::Scalar(2.0);
+        for (rows, cols) in [(0, 0), (3, 0), (0, 3), (1, 0)] {
+            let empty = Value::Matrix(Matrix::new(rows, cols));
+            assert_eq!(scalar(norm(&empty, Some(&two))), 0.0);
+            assert_eq!(scalar(norm(&empty, None)), 0.0);
+        }
+
+        let a = matrix(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        assert!((scalar(norm(&a, Some(&two))) - 5.464985704219043).abs() < 1e-12);
+    }
+
+    #[test]
+    fn cond_of_empty_matrix_is_zero() {
+        assert_eq!(scalar(cond(&Value::Matrix(Matrix::new(0, 0)))), 0.0);
+        assert_eq!(scalar(cond(&matrix(2, 2, &[2.0, 0.0, 0.0, 1.0]))), 2.0);
+        assert_eq!(
+            scalar(cond(&matrix(2, 2, &[1.0, 2.0, 2.0, 4.0]))),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn linsolve_transposes_row_vector_b() {
+        let a = matrix(2, 2, &[2.0, 1.0, 1.0, 3.0]);
+        let column = elements(linsolve(&a, &matrix(2, 1, &[3.0, 5.0])));
+        let row = elements(linsolve(&a, &matrix(1, 2, &[3.0, 5.0])));
+        assert_eq!(row.0, 2);
+        assert_eq!(row.1, 1);
+        for (x, y) in row.2.iter().zip(&column.2) {
+            assert!((x - y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn linsolve_rejects_mismatched_b() {
+        let a = matrix(2, 2, &[2.0, 1.0, 1.0, 3.0]);
+        assert_eq!(
+            error_message(linsolve(&a, &matrix(1, 3, &[1.0, 2.0, 3.0]))),
+            "La matriz b debe ser un vector columna de 2x1 (o un vector fila de 1x2), pero es de 1x3"
+        );
+        assert_eq!(
+            error_message(linsolve(&a, &matrix(3, 1, &[1.0, 2.0, 3.0]))),
+            "La cantidad de filas de A (2) y b (3) no coincide"
+        );
+    }
+
+    #[test]
+    fn eigenvalues_of_symmetric_matrices() {
+        let diagonal = matrix(3, 3, &[3., 0., 0., 0., -1., 0., 0., 0., 2.]);
+        let (_, _, mut values) = elements(eig(&diagonal));
+        values.sort_by(f64::total_cmp);
+        assert_eq!(values, [-1.0, 2.0, 3.0]);
+
+        // [2 1; 1 2] tiene autovalores 1 y 3.
+        let (rows, cols, mut values) = elements(eig(&matrix(2, 2, &[2., 1., 1., 2.])));
+        assert_eq!((rows, cols), (2, 1));
+        values.sort_by(f64::total_cmp);
+        assert!((values[0] - 1.0).abs() < 1e-12 && (values[1] - 3.0).abs() < 1e-12);
+
+        assert!(matches!(
+            eig(&matrix(2, 2, &[1., 2., 3., 4.])),
+            Err(EvalError::DomainError(_))
+        ));
+    }
+
+    #[test]
+    fn norms_of_vectors_and_matrices() {
+        let unit = matrix(1, 3, &[0.6, 0.0, -0.8]);
+        assert!((scalar(norm(&unit, None)) - 1.0).abs() < 1e-12);
+        assert!((scalar(norm(&unit, Some(&Value::Scalar(1.0)))) - 1.4).abs() < 1e-12);
+        assert_eq!(
+            scalar(norm(&unit, Some(&Value::Scalar(f64::INFINITY)))),
+            0.8
+        );
+
+        // ||[1 2; 3 4]||_F = sqrt(1 + 4 + 9 + 16) = sqrt(30)
+        let a = matrix(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        assert!((scalar(norm(&a, None)) - 30f64.sqrt()).abs() < 1e-12);
+        assert_eq!(scalar(norm(&a, Some(&Value::Scalar(1.0)))), 6.0);
+        assert_eq!(scalar(norm(&a, Some(&Value::Scalar(f64::INFINITY)))), 7.0);
+        assert!(norm(&a, Some(&Value::Scalar(3.0))).is_err());
+    }
+
+    #[test]
+    fn reshape_takes_elements_row_by_row() {
+        let a = matrix(2, 3, &[1., 2., 3., 4., 5., 6.]);
+        let (three, two) = (Value::Scalar(3.0), Value::Scalar(2.0));
+        assert_eq!(
+            elements(reshape(&a, &three, &two)),
+            (3, 2, vec![1., 2., 3., 4., 5., 6.])
+        );
+        assert_eq!(
+            error_message(reshape(&a, &three, &three)),
+            "La nueva dimensión debe tener la misma cantidad de elementos que la matriz"
+        );
+        let huge = Value::Scalar(1e10);
+        assert_eq!(
+            error_message(reshape(&Value::Scalar(1.0), &huge, &huge)),
+            "La nueva dimensión debe tener la misma cantidad de elementos que la matriz"
+        );
+    }
+
+    #[test]
+    fn kronecker_product() {
+        let b = matrix(2, 2, &[1., 2., 3., 4.]);
+        let eye = Value::Matrix(Matrix::identity(2));
+        #[rustfmt::skip]
+        let block_diagonal = vec![
+            1., 2., 0., 0.,
+            3., 4., 0., 0.,
+            0., 0., 1., 2.,
+            0., 0., 3., 4.,
+        ];
+        assert_eq!(elements(kron(&eye, &b)), (4, 4, block_diagonal));
+
+        let a = matrix(2, 2, &[1., -1., 0., 2.]);
+        #[rustfmt::skip]
+        let expected = vec![
+            1., 2., -1., -2.,
+            3., 4., -3., -4.,
+            0., 0., 2., 4.,
+            0., 0., 6., 8.,
+        ];
+        assert_eq!(elements(kron(&a, &b)), (4, 4, expected));
+    }
+
+    #[test]
+    fn minors_of_a_3x3_matrix() {
+        let a = matrix(3, 3, &[1., 2., 3., 4., 5., 6., 7., 8., 10.]);
+        let index = |i: f64| Value::Scalar(i);
+        // Menor (1, 1): det([5, 6; 8, 10]) = 2
+        assert!((scalar(minor(&a, &index(1.), &index(1.))) - 2.0).abs() < 1e-12);
+        // Menor (2, 3): det([1, 2; 7, 8]) = -6
+        assert!((scalar(minor(&a, &index(2.), &index(3.))) - -6.0).abs() < 1e-12);
+        // Menor (3, 2): det([1, 3; 4, 6]) = -6
+        assert!((scalar(minor(&a, &index(3.), &index(2.))) - -6.0).abs() < 1e-12);
+
+        assert_eq!(
+            error_message(minor(&a, &index(4.), &index(1.))),
+            "Los índices deben estar entre 1 y 3 (la matriz es de 3x3)"
+        );
+        assert!(minor(&matrix(2, 3, &[0.0; 6]), &index(1.), &index(1.)).is_err());
+    }
+
+    #[test]
+    fn seeded_rand_is_deterministic() {
+        let (m, n) = (Value::Scalar(3.0), Value::Scalar(4.0));
+        let first = elements(rand(&mut Rng::new(42), &m, &n));
+        let second = elements(rand(&mut Rng::new(42), &m, &n));
+        assert_eq!(first, second);
+        assert_eq!((first.0, first.1), (3, 4));
+        assert!(first.2.iter().all(|x| (0.0..1.0).contains(x)));
+
+        let other = elements(rand(&mut Rng::new(43), &m, &n));
+        assert_ne!(first, other);
+
+        let huge = Value::Scalar(1e10);
+        assert_eq!(
+            rand(&mut Rng::new(42), &huge, &huge).err(),
+            Some(EvalError::DomainError(
+                "La matriz es demasiado grande (puede tener hasta 10000000 elementos)".to_string()
+            ))
+        );
+        let big = Value::Scalar(1e5);
+        assert!(rand(&mut Rng::new(42), &big, &big).is_err());
+    }
+
+    #[test]
+    fn abs_is_element_wise() {
+        let a = matrix(2, 2, &[-1., 2., -3., -4.]);
+        assert_eq!(elements(abs(&a)), (2, 2, vec![1., 2., 3., 4.]));
+        assert_eq!(scalar(abs(&Value::Scalar(-2.5))), 2.5);
+        assert_eq!(
+            error_message(abs(&Value::Text("a".to_string()))),
+            "El valor absoluto no está definido para textos"
+        );
+    }
+
+    #[test]
+    fn sqrt_is_element_wise() {
+        let a = matrix(2, 2, &[4., 9., 16., 25.]);
+        assert_eq!(elements(sqrt(&a)), (2, 2, vec![2., 3., 4., 5.]));
+        assert_eq!(scalar(sqrt(&Value::Scalar(2.25))), 1.5);
+        assert!(matches!(
+            sqrt(&matrix(1, 2, &[4., -1.])),
+            Err(EvalError::DomainError(_))
+        ));
+    }
+
+    #[test]
+    fn trigonometric_functions_are_element_wise() {
+        let angles = matrix(1, 3, &[0.0, PI / 2.0, PI]);
+        assert_matrix(sin(&angles), 1, 3, &[0.0, 1.0, 0.0]);
+        assert_matrix(cos(&angles), 1, 3, &[1.0, 0.0, -1.0]);
+        assert_matrix(tan(&matrix(1, 2, &[0.0, PI / 4.0])), 1, 2, &[0.0, 1.0]);
+    }
+
+    #[test]
+    fn condition_numbers() {
+        let eye = Value::Matrix(Matrix::identity(3));
+        assert!((scalar(cond(&eye)) - 1.0).abs() < 1e-12);
+
+        // Casi singular: cond ≈ 4 / 0.0001
+        let ill = matrix(2, 2, &[1.0, 1.0, 1.0, 1.0001]);
+        let condition = scalar(cond(&ill));
+        assert!((condition - 40002.0).abs() < 1.0, "{}", condition);
+    }
+
+    #[test]
+    fn pseudoinverse_of_tall_matrix() {
+        let a = matrix(3, 2, &[1., 2., 3., 4., 5., 7.]);
+        assert_matrix(multiply(&pinv(&a).unwrap(), &a), 2, 2, &[1., 0., 0., 1.]);
+
+        let rank_one = matrix(3, 2, &[1., 2., 2., 4., 3., 6.]);
+        assert!(pinv(&rank_one).is_err());
+    }
+
+    #[test]
+    fn linsolve_fits_a_line_by_least_squares() {
+        // Recta y = c + m*x por los puntos (0, 1), (1, 2) y (2, 4).
+        let a = matrix(3, 2, &[1., 0., 1., 1., 1., 2.]);
+        let b = matrix(3, 1, &[1., 2., 4.]);
+        assert_matrix(linsolve(&a, &b), 2, 1, &[5.0 / 6.0, 1.5]);
+    }
+
+    #[test]
+    fn indeterminate_system_returns_its_solution_set() {
+        let a = matrix(2, 3, &[1., 2., -1., 0., 1., 3.]);
+        let b = matrix(2, 1, &[1., 2.]);
+        assert!(matches!(
+            linsolve(&a, &b),
+            Ok(Value::Text(solution)) if solution == "x1 = -3 + 7*x3\nx2 = 2 - 3*x3"
+        ));
+    }
+
+    #[test]
+    fn size_of_vectors_and_matrices() {
+        assert_eq!(
+            elements(size(&matrix(3, 1, &[1., 2., 3.]))),
+            (1, 2, vec![3., 1.])
+        );
+        assert_eq!(
+            elements(size(&matrix(1, 3, &[1., 2., 3.]))),
+            (1, 2, vec![1., 3.])
+        );
+        assert_eq!(
+            elements(size(&matrix(2, 3, &[0.0; 6]))),
+            (1, 2, vec![2., 3.])
+        );
+        assert_eq!(elements(size(&Value::Scalar(7.0))), (1, 2, vec![1., 1.]));
+    }
+
+    #[test]
+    fn numel_counts_elements() {
+        assert_eq!(scalar(numel(&matrix(2, 3, &[0.0; 6]))), 6.0);
+        assert_eq!(scalar(numel(&Value::Scalar(5.0))), 1.0);
+        assert_eq!(scalar(numel(&Value::Matrix(Matrix::new(0, 0)))), 0.0);
+    }
+
+    #[test]
+    fn shape_predicates() {
+        let empty = Value::Matrix(Matrix::new(0, 0));
+        let number = Value::Scalar(3.0);
+        let one_by_one = matrix(1, 1, &[3.0]);
+        let row = matrix(1, 3, &[1., 2., 3.]);
+        let column = matrix(3, 1, &[1., 2., 3.]);
+        let square = matrix(2, 2, &[1., 2., 3., 4.]);
+
+        let check = |a: &Value| {
+            (
+                boolean(isempty(a)),
+                boolean(isscalar(a)),
+                boolean(isvector(a)),
+            )
+        };
+        assert_eq!(check(&empty), (true, false, false));
+        assert_eq!(check(&number), (false, true, true));
+        assert_eq!(check(&one_by_one), (false, true, true));
+        assert_eq!(check(&row), (false, false, true));
+        assert_eq!(check(&column), (false, false, true));
+        assert_eq!(check(&square), (false, false, false));
+    }
+
+    #[test]
+    fn comparison_operators() {
+        let (one, two) = (Value::Scalar(1.0), Value::Scalar(2.0));
+        // Resultados de 1 op 2, 2 op 1 y 1 op 1
+        let check = |op: fn(&Value, &Value) -> FnResult| {
+            [
+                boolean(op(&one, &two)),
+                boolean(op(&two, &one)),
+                boolean(op(&one, &one)),
+            ]
+        };
+        assert_eq!(check(less), [true, false, false]);
+        assert_eq!(check(greater), [false, true, false]);
+        assert_eq!(check(less_equal), [true, false, true]);
+        assert_eq!(check(greater_equal), [false, true, true]);
+        assert_eq!(check(equal), [false, false, true]);
+        assert_eq!(check(not_equal), [true, true, false]);
+
+        // Se compara con tolerancia.
+        assert!(boolean(equal(
+            &Value::Scalar(0.1 + 0.2),
+            &Value::Scalar(0.3)
+        )));
+
+        // Con matrices, se compara elemento a elemento y se obtienen 0 y 1.
+        let a = matrix(1, 3, &[1., 2., 3.]);
+        assert_eq!(elements(less(&a, &two)), (1, 3, vec![1., 0., 0.]));
+    }
+
+    #[test]
+    fn fractional_power_of_spd_matrix() {
+        let a = matrix(2, 2, &[4., 1., 1., 3.]);
+        let root = pow(&a, &Value::Scalar(0.5)).unwrap();
+        assert_matrix(pow(&root, &Value::Scalar(2.0)), 2, 2, &[4., 1., 1., 3.]);
+
+        let non_symmetric = matrix(2, 2, &[4., 1., 0., 3.]);
+        assert!(pow(&non_symmetric, &Value::Scalar(0.5)).is_err());
+    }
+
+    #[test]
+    fn factorial_of_large_numbers() {
+        let factorial_of = |n: f64| scalar(factorial(&Value::Scalar(n)));
+        assert_eq!(factorial_of(20.0), 2432902008176640000.0);
+        // Desde 21! no entra en un entero de 64 bits, pero sí en un f64.
+        assert_eq!(factorial_of(21.0), 51090942171709440000.0);
+        let f25 = factorial_of(25.0);
+        assert!((f25 / 1.5511210043330986e25 - 1.0).abs() < 1e-15);
+        assert!(factorial_of(170.0).is_finite());
+        assert_eq!(factorial_of(171.0), f64::INFINITY);
+        // Los no enteros se extienden con la función gamma: 0.5! = sqrt(pi)/2
+        assert!((factorial_of(0.5) - PI.sqrt() / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn combinations() {
+        let choose = |n: f64, k: f64| scalar(nchoosek(&Value::Scalar(n), &Value::Scalar(k)));
+        assert_eq!(choose(5.0, 2.0), 10.0);
+        assert_eq!(choose(0.0, 0.0), 1.0);
+        for k in 0..=12 {
+            assert_eq!(choose(12.0, k as f64), choose(12.0, (12 - k) as f64));
+        }
+        assert_eq!(choose(60.0, 30.0), 118264581564861424.0);
+        assert!(nchoosek(&Value::Scalar(2.0), &Value::Scalar(3.0)).is_err());
+
+        assert_eq!(choose(2000.0, 1000.0), f64::INFINITY);
+        assert_eq!(choose(2e12, 1e12), f64::INFINITY);
+        assert!(matches!(
+            nchoosek(&Value::Scalar(1e20), &Value::Scalar(2.0)),
+            Err(EvalError::DomainError(_))
+        ));
+    }
+
+    #[test]
+    fn real_roots_of_negative_numbers() {
+        assert_eq!(scalar(cbrt(&Value::Scalar(-8.0))), -2.0);
+        let root = |x: f64, n: f64| nthroot(&Value::Scalar(x), &Value::Scalar(n));
+        assert!((scalar(root(-32.0, 5.0)) - -2.0).abs() < 1e-12);
+        assert!((scalar(root(81.0, 4.0)) - 3.0).abs() < 1e-12);
+        // Una raíz par de un número negativo no es real.
+        assert!(matches!(root(-16.0, 4.0), Err(EvalError::DomainError(_))));
+    }
+
+    #[test]
+    fn power_edge_cases() {
+        let power = |a: f64, n: f64| pow(&Value::Scalar(a), &Value::Scalar(n));
+        assert_eq!(scalar(power(0.0, 0.0)), 1.0);
+        assert!(matches!(
+            power(-8.0, 1.0 / 3.0),
+            Err(EvalError::DomainError(_))
+        ));
+        assert_eq!(scalar(power(-2.0, 3.0)), -8.0);
+        assert_eq!(scalar(power(4.0, 0.5)), 2.0);
+    }
+
+    #[test]
+    fn factorial_rejects_invalid_inputs() {
+        for n in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(
+                error_message(factorial(&Value::Scalar(n))),
+                "El factorial solo está definido para números finitos"
+            );
+        }
+        assert!(matches!(
+            factorial(&Value::Scalar(-1.0)),
+            Err(EvalError::DomainError(_))
+        ));
+        // Los números enormes no se recorren uno por uno.
+        assert_eq!(scalar(factorial(&Value::Scalar(1e300))), f64::INFINITY);
+        assert_eq!(
+            scalar(factorial(&Value::Scalar(u32::MAX as f64 + 2.0))),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn tangent_is_undefined_at_odd_multiples_of_half_pi() {
+        for x in [PI / 2.0, -PI / 2.0, 3.0 * PI / 2.0] {
+            assert_eq!(
+                error_message(tan(&Value::Scalar(x))),
+                "La tangente no está definida en los múltiplos impares de pi/2"
+            );
+        }
+        assert!(tan(&matrix(1, 2, &[0.0, PI / 2.0])).is_err());
+        assert!((scalar(tan(&Value::Scalar(PI / 2.0 - 1e-6))) - 1e6).abs() < 1.0);
+    }
+
+    #[test]
+    fn trigonometric_functions_in_degrees() {
+        let at = |f: fn(&Value) -> FnResult, x: f64| scalar(f(&Value::Scalar(x)));
+        assert_eq!(at(sind, 0.0), 0.0);
+        assert_eq!(at(sind, 90.0), 1.0);
+        assert_eq!(at(sind, 180.0), 0.0);
+        assert_eq!(at(sind, 270.0), -1.0);
+        assert_eq!(at(sind, -90.0), -1.0);
+        assert!((at(sind, 30.0) - 0.5).abs() < 1e-15);
+        assert_eq!(at(cosd, 0.0), 1.0);
+        assert_eq!(at(cosd, 90.0), 0.0);
+        assert_eq!(at(cosd, 180.0), -1.0);
+        assert!((at(cosd, 60.0) - 0.5).abs() < 1e-15);
+        assert_eq!(at(tand, 45.0), 1.0);
+        assert_eq!(at(tand, 135.0), -1.0);
+        assert_eq!(at(tand, 180.0), 0.0);
+        assert!(tand(&Value::Scalar(90.0)).is_err());
+    }
+
+    #[test]
+    fn scalars_broadcast_in_addition_and_subtraction() {
+        let a = matrix(2, 2, &[1., 2., 3., 4.]);
+        assert_eq!(
+            elements(add(&a, &Value::Scalar(1.0))),
+            (2, 2, vec![2., 3., 4., 5.])
+        );
+        assert_eq!(
+            elements(subtract(&Value::Scalar(10.0), &matrix(1, 2, &[1., 2.]))),
+            (1, 2, vec![9., 8.])
+        );
+        assert_eq!(
+            elements(subtract(&matrix(1, 2, &[1., 2.]), &Value::Scalar(10.0))),
+            (1, 2, vec![-9., -8.])
+        );
+    }
+
+    #[test]
+    fn isequal_compares_with_tolerance() {
+        let a = matrix(2, 2, &[1., 2., 3., 4.]);
+        assert!(boolean(isequal(&a, &a.clone())));
+        let perturbed = matrix(2, 2, &[1. + 1e-14, 2., 3., 4. - 1e-14]);
+        assert!(boolean(isequal(&a, &perturbed)));
+        assert!(!boolean(isequal(&a, &matrix(2, 2, &[1., 2., 3., 4.001]))));
+        assert!(!boolean(isequal(&a, &matrix(1, 4, &[1., 2., 3., 4.]))));
+
+        assert!(boolean(isequal(
+            &Value::Scalar(0.3),
+            &Value::Scalar(0.1 + 0.2)
+        )));
+        assert!(boolean(isequal(&Value::Scalar(5.0), &matrix(1, 1, &[5.0]))));
+        assert!(!boolean(isequal(&Value::Scalar(5.0), &a)));
+    }
+
+    #[test]
+    fn repmat_tiles_a_matrix() {
+        let a = matrix(1, 2, &[1., 2.]);
+        assert_eq!(
+            elements(repmat(&a, &Value::Scalar(2.0), &Value::Scalar(2.0))),
+            (2, 4, vec![1., 2., 1., 2., 1., 2., 1., 2.])
+        );
+        assert_eq!(
+            elements(repmat(
+                &Value::Scalar(7.0),
+                &Value::Scalar(1.0),
+                &Value::Scalar(3.0)
+            )),
+            (1, 3, vec![7., 7., 7.])
+        );
+        assert!(repmat(&a, &Value::Scalar(-1.0), &Value::Scalar(2.0)).is_err());
+        assert!(repmat(&a, &Value::Scalar(1.5), &Value::Scalar(2.0)).is_err());
+        let huge = Value::Scalar(1e10);
+        assert!(matches!(
+            repmat(&Value::Scalar(1.0), &huge, &huge),
+            Err(EvalError::DomainError(_))
+        ));
+        assert!(matches!(
+            repmat(&Value::Matrix(Matrix::new(0, 2)), &huge, &huge),
+            Err(EvalError::DomainError(_))
+        ));
+    }
+
+    #[test]
+    fn matrix_power_errors_explain_the_cause() {
+        let singular = matrix(2, 2, &[1., 2., 2., 4.]);
+        assert_eq!(
+            error_message(pow(&singular, &Value::Scalar(-1.0))),
+            "La matriz no se puede elevar a un exponente negativo porque no tiene inversa (su determinante es 0)"
+        );
+        let non_symmetric = matrix(2, 2, &[1., 2., 3., 4.]);
+        assert!(error_message(pow(&non_symmetric, &Value::Scalar(1.5)))
+            .starts_with("Las potencias con exponente no entero solo están implementadas para matrices simétricas"));
+    }
+
+    #[test]
+    fn power_of_non_square_matrix_explains_why() {
+        assert_eq!(
+            error_message(pow(&matrix(1, 3, &[1., 2., 3.]), &Value::Scalar(2.0))),
+            "La potencia A^n es el producto A*A*...*A, por lo que solo está definida para matrices cuadradas (esta es de 1x3)"
+        );
+    }
+
+    #[test]
+    fn class_names_each_type() {
+        let name = |x: &Value| match class(x) {
+            Ok(Value::Text(name)) => name,
+            _ => panic!("se esperaba un texto"),
+        };
+        assert_eq!(name(&Value::Scalar(1.0)), "double");
+        assert_eq!(name(&matrix(1, 2, &[1., 2.])), "double");
+        assert_eq!(name(&Value::Bool(true)), "logical");
+        assert_eq!(name(&Value::Text("a".to_string())), "char");
+    }
+
+    #[test]
+    fn if_selects_a_branch() {
+        let (a, b) = (Value::Scalar(1.0), matrix(1, 2, &[2., 3.]));
+        assert_eq!(scalar(if_else(&Value::Bool(true), &a, &b)), 1.0);
+        assert_eq!(
+            elements(if_else(&Value::Bool(false), &a, &b)),
+            (1, 2, vec![2., 3.])
+        );
+        assert_eq!(scalar(if_else(&Value::Scalar(0.0), &b, &a)), 1.0);
+        assert!(if_else(&matrix(1, 2, &[1., 1.]), &a, &b).is_err());
+    }
+
+    #[test]
+    fn clamp_restricts_to_the_interval() {
+        let (lo, hi) = (Value::Scalar(0.0), Value::Scalar(10.0));
+        assert_eq!(scalar(clamp(&Value::Scalar(-5.0), &lo, &hi)), 0.0);
+        assert_eq!(scalar(clamp(&Value::Scalar(3.5), &lo, &hi)), 3.5);
+        assert_eq!(scalar(clamp(&Value::Scalar(12.0), &lo, &hi)), 10.0);
+        assert_eq!(
+            elements(clamp(&matrix(1, 3, &[-1., 5., 11.]), &lo, &hi)),
+            (1, 3, vec![0., 5., 10.])
+        );
+        assert!(clamp(&Value::Scalar(1.0), &hi, &lo).is_err());
+    }
+
+    #[test]
+    fn hypot_does_not_overflow() {
+        assert_eq!(scalar(hypot(&Value::Scalar(3.0), &Value::Scalar(4.0))), 5.0);
+        // 3e200^2 no entra en un f64, pero el resultado sí.
+        let (a, b) = (Value::Scalar(3e200), Value::Scalar(4e200));
+        assert!((scalar(hypot(&a, &b)) / 5e200 - 1.0).abs() < 1e-15);
+        assert!(hypot(&matrix(1, 2, &[3., 4.]), &b).is_err());
+    }
+
+    #[test]
+    fn degrees_and_radians_conversion() {
+        assert!((scalar(deg2rad(&Value::Scalar(180.0))) - PI).abs() < 1e-15);
+        assert!((scalar(rad2deg(&Value::Scalar(PI))) - 180.0).abs() < 1e-12);
+
+        let angles = matrix(1, 4, &[0., 45., -90., 360.]);
+        let round_trip = rad2deg(&deg2rad(&angles).unwrap());
+        assert_matrix(round_trip, 1, 4, &[0., 45., -90., 360.]);
     }
 }
@@ -0,0 +1,137 @@
+// En este archivo se implementa la lectura de la entrada del usuario.
+// Si se está usando una terminal, se usa `rustyline` para poder editar la línea y
+// navegar el historial con las flechas. Si no (por ejemplo, si la entrada viene de
+// un archivo o de otro programa), se lee directamente de la entrada estándar.
+// Excede a la materia.
+
+use rustyline::{error::ReadlineError, DefaultEditor};
+use std::{
+    io::{stdin, stdout, IsTerminal, Write},
+    path::PathBuf,
+};
+
+/// Resultado de leer una línea.
+pub enum ReadResult {
+    /// Se leyó una línea (sin el salto de línea final).
+    Line(String),
+    /// El usuario canceló la línea actual (Ctrl-C).
+    Interrupted,
+    /// Se llegó al final de la entrada (Ctrl-D).
+    Eof,
+    /// Ocurrió un error al leer.
+    Error(String),
+}
+
+pub enum Input {
+    /// Lectura con edición de línea e historial.
+    Editor {
+        editor: Box<DefaultEditor>,
+        history: Option<PathBuf>,
+    },
+    /// Lectura directa de la entrada estándar.
+    Plain,
+}
+
+impl Input {
+    /// Crea un lector de entrada, usando `rustyline` solo si la entrada es una terminal.
+    pub fn new() -> Input {
+        if !stdin().is_terminal() {
+            return Input::Plain;
+        }
+
+        match DefaultEditor::new() {
+            Ok(mut editor) => {
+                let history = history_path();
+                if let Some(path) = &history {
+                    // Si el archivo no existe todavía, no pasa nada.
+                    editor.load_history(path).ok();
+                }
+                Input::Editor {
+                    editor: Box::new(editor),
+                    history,
+                }
+            }
+            Err(_) => Input::Plain,
+        }
+    }
+
+    /// Muestra `prompt` y lee una línea.
+    pub fn read_line(&mut self, prompt: &str) -> ReadResult {
+        match self {
+            Input::Editor { editor, history } => match editor.readline(prompt) {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        editor.add_history_entry(line.as_str()).ok();
+                        if let Some(path) = history {
+                            editor.save_history(path).ok();
+                        }
+                    }
+                    ReadResult::Line(line)
+                }
+                Err(ReadlineError::Interrupted) => ReadResult::Interrupted,
+                Err(ReadlineError::Eof) => ReadResult::Eof,
+                Err(e) => ReadResult::Error(e.to_string()),
+            },
+            Input::Plain => {
+                print!("{}", prompt);
+                stdout().flush().ok();
+                let mut line = String::new();
+                match stdin().read_line(&mut line) {
+                    Ok(0) => ReadResult::Eof,
+                    Ok(_) => ReadResult::Line(line),
+                    Err(e) => ReadResult::Error(e.to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// Limpia la consola (comando `clc`).
+pub fn clear_screen() {
+    print!("{}", clear_sequence(stdout().is_terminal()));
+    stdout().flush().ok();
+}
+
+/// Secuencia de escape ANSI que limpia la consola.
+/// En una terminal, además de limpiar la pantalla (`ESC[2J`) y mover el cursor al
+/// inicio (`ESC[H`), se borra el historial de desplazamiento (`ESC[3J`), para que no
+/// se pueda volver a lo anterior subiendo. Si la salida no es una terminal (por
+/// ejemplo, se redirige a un archivo), solo se limpia la pantalla.
+fn clear_sequence(is_terminal: bool) -> &'static str {
+    if is_terminal {
+        "\x1b[3J\x1b[2J\x1b[H"
+    } else {
+        "\x1b[2J\x1b[1;1H"
+    }
+}
+
+/// Ruta del archivo donde se guarda el historial (`~/.matec_history`).
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".matec_history"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_scrollback_only_on_terminals() {
+        assert!(clear_sequence(true).starts_with("\x1b[3J"));
+        assert!(clear_sequence(true).contains("\x1b[2J"));
+        assert!(!clear_sequence(false).contains("\x1b[3J"));
+        assert!(clear_sequence(false).contains("\x1b[2J"));
+    }
+
+    #[test]
+    fn history_is_kept_in_the_home_directory() {
+        // Si no hay un directorio personal, simplemente no se guarda el historial.
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(
+                history_path(),
+                Some(PathBuf::from(home).join(".matec_history"))
+            );
+        }
+    }
+}
@@ -1,118 +1,412 @@
+#[macro_use]
+mod diary;
+mod error;
 mod functions;
+mod input;
 mod matrix;
 mod parser;
+mod random;
+mod registry;
 mod utils;
 mod value;
+mod workspace;
 
+use error::EvalError;
+use input::{clear_screen, Input, ReadResult};
 use matrix::Matrix;
+use parser::Rule;
 use parser::{parse, AstNode};
-use pest::error::InputLocation;
-use std::{
-    collections::HashMap,
-    io::{stdin, stdout, Write},
-};
-use value::Value;
+use pest::error::{Error, ErrorVariant, InputLocation, LineColLocation};
+use random::Rng;
+use std::collections::HashMap;
+use value::{Function, Value};
 
 type Variables = HashMap<String, Value>;
 
+/// Máxima cantidad de caracteres de la entrada que se muestran al marcar un error de sintáxis.
+const ERROR_CONTEXT: usize = 60;
+
+/// Caché de resultados costosos (como `det(A)` o `inv(A)`) aplicados a variables.
+/// La clave es el par (función, variable) y se guarda junto al hash del contenido
+/// de la matriz, para nunca devolver un resultado viejo.
+type Cache = HashMap<(String, String), (u64, Value)>;
+
+/// Variables predefinidas al iniciar el programa. No se borran con `clear`.
+const CONSTANTS: [(&str, f64); 4] = [
+    ("pi", std::f64::consts::PI),
+    ("e", std::f64::consts::E),
+    ("inf", f64::INFINITY),
+    ("nan", f64::NAN),
+];
+
 fn main() {
     // En este hashmap se guardan las variables que se van creando.
     let mut variables: Variables = HashMap::new();
+    // En este hashmap se guardan los resultados de funciones costosas.
+    let mut cache: Cache = HashMap::new();
+    // Generador de números aleatorios, usado por rand().
+    let mut rng = Rng::from_time();
 
-    // Agregamos las variables pi y e.
-    variables.insert("pi".to_string(), Value::Scalar(std::f64::consts::PI));
-    variables.insert("e".to_string(), Value::Scalar(std::f64::consts::E));
+    // Agregamos las variables predefinidas (pi, e, inf y nan).
+    define_constants(&mut variables);
 
     // Impresión del mensaje de bienvenida.
     println!("#=========================#");
     println!("# Operaciones de Matrices #");
     println!("#=========================#");
-    println!("");
+    println!();
     println!("Por Majoros, Lorenzo; y Seery, Juan Martín");
     println!("Para Matemática C - 2023");
-    println!("");
+    println!();
     println!("Para ver los comandos disponibles, escriba \"help\"");
     println!("Para salir, escriba \"exit\"");
-    println!("");
-    println!("");
+    println!();
+    println!();
+
+    // Lector de la entrada del usuario (ver input.rs)
+    let mut reader = Input::new();
 
     loop {
         // Se lee la entrada del usuario.
-        print!("> ");
-        let mut input = String::new();
-        stdout().flush().unwrap();
-        stdin().read_line(&mut input).unwrap();
+        let input = match read_input(|prompt| reader.read_line(prompt)) {
+            ReadResult::Line(line) => line,
+            // Con Ctrl-C se cancela la línea actual.
+            ReadResult::Interrupted => continue,
+            // Se llegó al final de la entrada (Ctrl-D), se termina el programa como con `exit`.
+            ReadResult::Eof => {
+                output!();
+                break;
+            }
+            ReadResult::Error(e) => {
+                output!("Error al leer la entrada: {}", e);
+                break;
+            }
+        };
         let input = input.trim();
+        diary::write(&format!("> {}", input));
 
         // Casos especiales de comandos.
         if input == "exit" {
             break;
         } else if input == "clc" {
-            print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
+            clear_screen();
             continue;
         } else if input == "?" || input == "help" {
             show_menu();
             continue;
+        } else if input == "format" {
+            // Se vuelve al formato por defecto.
+            utils::set_long_format(false);
+            utils::set_bracket_format(false);
+            continue;
+        } else if input == "format short" {
+            utils::set_long_format(false);
+            continue;
+        } else if input == "format long" {
+            utils::set_long_format(true);
+            continue;
+        } else if input == "format brackets" {
+            utils::set_bracket_format(true);
+            continue;
+        } else if input == "format plain" {
+            utils::set_bracket_format(false);
+            continue;
+        } else if input == "diary on" || input == "diary" {
+            if let Err(e) = diary::start("diary") {
+                output!("Error: {}", e);
+            }
+            continue;
+        } else if input == "diary off" {
+            diary::stop();
+            continue;
+        } else if let Some(path) = input.strip_prefix("diary ") {
+            // El nombre del archivo puede estar entre comillas: diary "sesion.txt"
+            if let Err(e) = diary::start(path.trim().trim_matches('"')) {
+                output!("Error: {}", e);
+            }
+            continue;
+        } else if let Some(seed) = input.strip_prefix("seed ") {
+            match seed.trim().parse::<u64>() {
+                Ok(seed) => rng = Rng::new(seed),
+                Err(_) => output!("Error: La semilla debe ser un número entero no negativo"),
+            }
+            continue;
+        } else if input == "who" {
+            output!("{}", list_variables(&variables, false));
+            continue;
+        } else if input == "whos" {
+            output!("{}", list_variables(&variables, true));
+            continue;
+        } else if let Some(path) = input.strip_prefix("save ") {
+            if let Err(e) = workspace::save(&variables, path.trim()) {
+                output!("Error: {}", e);
+            }
+            continue;
+        } else if let Some(path) = input.strip_prefix("load ") {
+            match workspace::load(path.trim()) {
+                Ok(loaded) => {
+                    for (name, value) in loaded {
+                        // Las constantes, ans y los nombres de funciones no se pueden
+                        // pisar, igual que al asignar.
+                        if let Err(e) = check_assignable(Some(&name)) {
+                            output!("Error: {}", e);
+                            continue;
+                        }
+                        cache.retain(|(_, var), _| *var != name);
+                        variables.insert(name, value);
+                    }
+                }
+                Err(e) => output!("Error: {}", e),
+            }
+            continue;
+        } else if input == "clear" {
+            clear_variables(&mut variables);
+            cache.clear();
+            continue;
+        } else if let Some(names) = input.strip_prefix("clear ") {
+            // Se pueden borrar varias variables a la vez: `clear x y z`
+            for name in names.split_whitespace() {
+                if is_constant(name) {
+                    output!("No se puede borrar \"{}\" porque es una constante", name);
+                    continue;
+                }
+                if variables.remove(name).is_none() {
+                    output!("La variable \"{}\" no está definida", name);
+                }
+                cache.retain(|(_, var), _| var != name);
+            }
+            continue;
         }
 
         // Se parsea la entrada en texto a un AST (ver parser/mod.rs)
-        match parse(&input) {
+        match parse(input) {
             // Si no hay errores de sintáxis, se evalúa cada expresión.
             Ok(ast) => {
-                let len = ast.len();
-                for i in 0..len {
+                for statement in &ast {
                     // Si la expresión tiene asignación (x = ...), se toma el nombre de la variable.
                     // De lo contrario, se asigna a la variable "ans".
-                    let assign_to = &ast[i].assign_to.clone().unwrap_or("ans".to_string());
-                    let expr = &ast[i].expr;
-                    // Se evalúa la expresión.
-                    match evaluate_expression(expr, &variables) {
+                    let assign_to = &statement.assign_to.clone().unwrap_or("ans".to_string());
+                    let expr = &statement.expr;
+                    // Un disp(x) suelto ya muestra su argumento, así que no se vuelve a
+                    // mostrar ni se guarda en "ans".
+                    let is_disp = statement.assign_to.is_none()
+                        && matches!(expr, AstNode::Call { func, .. } if func == "disp");
+                    // Se evalúa la expresión, siempre que se pueda asignar a esa variable.
+                    let result = check_assignable(statement.assign_to.as_deref())
+                        .and_then(|_| evaluate_expression(expr, &variables, &mut cache, &mut rng));
+                    match result {
+                        Ok(_) if is_disp => {}
                         Ok(ans) => {
-                            if i + 1 == len {
-                                // Si es la última expresión, se imprime el resultado.
-                                println!("{} = {}", assign_to, ans);
+                            if !statement.silent {
+                                // Si no termina en ";", se imprime el resultado.
+                                output!("{} = {}", assign_to, ans);
                             }
                             // Se guarda el resultado en el hashmap de variables.
                             variables.insert(assign_to.to_string(), ans);
+                            // Se invalidan los resultados guardados de la variable reasignada.
+                            cache.retain(|(_, var), _| var != assign_to);
                         }
-                        Err(e) => {
-                            println!("Error: {}", e);
-                            break;
-                        }
+                        // Si hay un error, se informa y se sigue con la siguiente sentencia.
+                        // La variable (o "ans") conserva su valor anterior.
+                        Err(e) => output!("Error: {}", e),
                     }
                 }
             }
             // Si hay un error de sintáxis, se imprime el error.
-            Err(e) => {
-                match e.location {
-                    InputLocation::Pos(pos) => {
-                        println!("  {}^", " ".repeat(pos));
-                    }
-                    InputLocation::Span((start, end)) => {
-                        println!("  {}{}", " ".repeat(start), "".repeat(end - start));
-                    }
-                }
-                println!("Error de sintáxis. Verifique que la expresión esté bien escrita.");
+            Err(e) => output!("{}", syntax_error_message(input, &e)),
+        };
+    }
+}
+
+/// Lee una entrada del usuario. Se siguen leyendo líneas (y se unen) mientras:
+/// - la línea termine en `...`, como en MATLAB;
+/// - queden corchetes o paréntesis sin cerrar, así se puede escribir una matriz en
+///   varias líneas. Dentro de una matriz, cada salto de línea separa filas (como `;`).
+///   Una línea vacía cancela la entrada.
+///
+/// `read_line` muestra el indicador que recibe y lee una línea (ver input.rs).
+fn read_input(mut read_line: impl FnMut(&str) -> ReadResult) -> ReadResult {
+    let mut input = match read_line("> ") {
+        ReadResult::Line(line) => line,
+        result => return result,
+    };
+    loop {
+        // Se calcula lo leído hasta ahora y con qué se une a la próxima línea.
+        // Los comentarios se descartan, porque al unir las líneas taparían el resto.
+        let code = strip_comment(&input);
+        let unclosed = unclosed_brackets(code);
+        let (start, separator) = if let Some(start) = strip_continuation(code) {
+            (start.to_string(), " ")
+        } else if let Some(&bracket) = unclosed.last() {
+            let start = code.trim_end().to_string();
+            let separator = if bracket == '[' && !start.ends_with(['[', ',', ';']) {
+                "; "
+            } else {
+                " "
+            };
+            (start, separator)
+        } else {
+            return ReadResult::Line(input);
+        };
+
+        input = match read_line("... ") {
+            ReadResult::Line(line) if line.trim().is_empty() && !unclosed.is_empty() => {
+                return ReadResult::Interrupted
             }
+            ReadResult::Line(line) => format!("{}{}{}", start, separator, line.trim()),
+            // Si la entrada termina en medio de una continuación, se usa lo leído.
+            ReadResult::Eof => return ReadResult::Line(start),
+            result => return result,
         };
     }
 }
 
+/// Si la línea termina en `...`, devuelve lo que está antes. Si no, `None`.
+fn strip_continuation(line: &str) -> Option<&str> {
+    line.trim_end().strip_suffix("...")
+}
+
+/// Devuelve la línea sin el comentario (lo que sigue a un % que no está dentro de
+/// un texto).
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if in_string {
+            // Dentro de un texto, una barra invertida escapa al caracter siguiente,
+            // que puede ser una comilla.
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '%' {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+/// Devuelve los corchetes y paréntesis abiertos que no se cerraron en `input`, en el
+/// orden en que se abrieron. No se tienen en cuenta los que están dentro de textos
+/// ("[") ni en comentarios.
+fn unclosed_brackets(input: &str) -> Vec<char> {
+    let mut open = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in strip_comment(input).chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' | '(' => open.push(c),
+            ']' | ')' => {
+                // Si hay más cierres que aperturas, el error se informa al parsear.
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    open
+}
+
+/// Verifica que se pueda asignar a la variable `name`. No se permite asignar a las
+/// constantes (pi, e, inf, nan), a "ans" (que solo guarda el último resultado) ni a
+/// los nombres de las funciones, para no confundirlos.
+fn check_assignable(name: Option<&str>) -> Result<(), EvalError> {
+    let Some(name) = name else {
+        return Ok(());
+    };
+    if is_constant(name) {
+        return Err(EvalError::Other(format!(
+            "No se puede asignar a \"{}\" porque es una constante",
+            name
+        )));
+    }
+    if name == "ans" {
+        return Err(EvalError::Other(
+            "No se puede asignar a \"ans\" porque guarda el resultado de la última operación"
+                .to_string(),
+        ));
+    }
+    if registry::get(name).is_some() {
+        return Err(EvalError::Other(format!(
+            "No se puede asignar a \"{}\" porque es el nombre de una función",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Busca la función con el nombre más parecido a `name`, para sugerirla cuando
+/// se escribe mal. Solo se tienen en cuenta las que están a pocos cambios: uno cada
+/// tres letras (y al menos uno), así los nombres cortos no sugieren cualquier cosa.
+fn closest_function(name: &str) -> Option<&'static str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    registry::BUILTINS
+        .iter()
+        .map(|builtin| (utils::levenshtein(name, builtin.name), builtin.name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, function)| function)
+}
+
 /// Evalúa una expresión y devuelve el resultado.
 /// Esta es una función recursiva que evalúa cada nodo del AST.
 /// Puede devolver un error si la expresión no es válida.
-fn evaluate_expression(expr: &AstNode, variables: &Variables) -> Result<Value, String> {
+fn evaluate_expression(
+    expr: &AstNode,
+    variables: &Variables,
+    cache: &mut Cache,
+    rng: &mut Rng,
+) -> Result<Value, EvalError> {
     match expr {
         // Si el nodo es una variable, se busca en el hashmap de variables.
         AstNode::Ident(s) => {
             if let Some(v) = variables.get(s) {
                 Ok(v.clone())
             } else {
-                Err(format!("La variable \"{}\" no está definida", s))
+                Err(EvalError::UndefinedVariable(s.clone()))
             }
         }
         // Si el nodo es un número, se devuelve el valor.
         AstNode::Scalar(n) => Ok(Value::Scalar(*n)),
+        // Si el nodo es un texto, se devuelve tal cual.
+        AstNode::Str(text) => Ok(Value::Text(text.clone())),
+        // Si el nodo es una función anónima, se guardan los valores actuales de las
+        // variables que usa (salvo sus parámetros).
+        AstNode::Lambda {
+            params,
+            body,
+            source,
+        } => {
+            let mut names = Vec::new();
+            body.collect_names(&mut names);
+            let captured = names
+                .into_iter()
+                .filter(|name| !params.contains(name))
+                .filter_map(|name| {
+                    let value = variables.get(&name)?.clone();
+                    Some((name, value))
+                })
+                .collect();
+            Ok(Value::Function(Function {
+                params: params.clone(),
+                body: (**body).clone(),
+                captured,
+                source: source.clone(),
+            }))
+        }
         // Si el nodo es una matriz, se pasa a Matrix.
         AstNode::Matrix(vec) => {
             // Se recibe un vector de vectores de nodos. Vec<Vec<AstNode>>
@@ -122,6 +416,10 @@ fn evaluate_expression(expr: &AstNode, variables: &Variables) -> Result<Value, S
             // vec![vec![AstNode::Scalar(1), AstNode::Scalar(2)], vec![AstNode::Scalar(3), AstNode::Scalar(4)]]
 
             // Hay que verificar que la matriz esté bien declarada.
+            // Las filas vacías (como la del final de [1, 2;]) se ignoran, igual que en
+            // Matrix::from_2d. Así, [] y [;] son matrices vacías de 0x0.
+            let vec: Vec<&Vec<AstNode>> = vec.iter().filter(|row| !row.is_empty()).collect();
+
             // Primero, se verifica el caso de una matriz vacía.
             let rows = vec.len();
             if rows == 0 {
@@ -138,10 +436,10 @@ fn evaluate_expression(expr: &AstNode, variables: &Variables) -> Result<Value, S
                 // Si una fila tiene una cantidad distinta de columnas a la primera fila,
                 // se devuelve un error.
                 if row.len() != cols {
-                    return Err(
+                    return Err(EvalError::DimensionMismatch(
                         "La matriz está mal declarada: el número de columnas no es consistente"
                             .to_string(),
-                    );
+                    ));
                 }
 
                 // Se itera cada columna de la fila.
@@ -151,14 +449,21 @@ fn evaluate_expression(expr: &AstNode, variables: &Variables) -> Result<Value, S
                     // donde 5*4 es una expresión que se evalúa recursivamente.
 
                     // Se evalúa la expresión y se guarda en la matriz.
-                    match evaluate_expression(col, variables) {
-                        Ok(Value::Scalar(n)) => matrix.set(i, j, n).unwrap(),
-                        Ok(Value::Matrix(_)) => {
-                            return Err(
-                                "No se puede declarar una matriz dentro de otra matriz".to_string()
-                            )
+                    let value = evaluate_expression(col, variables, cache, rng)?;
+                    if let Value::Matrix(_) = value {
+                        return Err(EvalError::TypeError(
+                            "No se puede declarar una matriz dentro de otra matriz".to_string(),
+                        ));
+                    }
+                    // Cualquier otro tipo de valor que no sea un número no puede formar
+                    // parte de una matriz.
+                    match value.as_scalar() {
+                        Some(n) => matrix.set(i, j, n).unwrap(),
+                        None => {
+                            return Err(EvalError::TypeError(
+                                "Los elementos de una matriz deben ser números".to_string(),
+                            ))
                         }
-                        Err(e) => return Err(e),
                     };
                 }
             }
@@ -168,19 +473,30 @@ fn evaluate_expression(expr: &AstNode, variables: &Variables) -> Result<Value, S
         // Se encontró un operador unario. (Como -5, o 5!)
         // Todas funciones unarias se encuentran en functions/mod.rs
         AstNode::UnaryOp { op, expr } => {
-            let value = evaluate_expression(expr, variables)?;
+            let value = evaluate_expression(expr, variables, cache, rng)?;
             match op {
                 parser::UnaryOp::Positive => Ok(value),
-                parser::UnaryOp::Negate => functions::negate(&value),
-                parser::UnaryOp::Factorial => functions::factorial(&value),
-                parser::UnaryOp::Transpose => functions::transpose(&value),
+                // Los booleanos se operan como 1 (verdadero) o 0 (falso).
+                parser::UnaryOp::Negate => functions::negate(&value.into_numeric()),
+                parser::UnaryOp::Not => functions::not(&value),
+                parser::UnaryOp::Factorial => functions::factorial(&value.into_numeric()),
+                // Como todavía no hay números complejos, la traspuesta conjugada (')
+                // y la traspuesta sin conjugar (.') son iguales.
+                parser::UnaryOp::Transpose | parser::UnaryOp::TransposeNoConj => {
+                    functions::transpose(&value)
+                }
             }
         }
         // Se encontró un operador binbario. (Como 4-5, o 3^2)
         // Todas las funciones binarias se encuentran en functions/mod.rs
         AstNode::BinaryOp { left, op, right } => {
-            let left = evaluate_expression(left, variables)?;
-            let right = evaluate_expression(right, variables)?;
+            let left = evaluate_expression(left, variables, cache, rng)?;
+            let right = evaluate_expression(right, variables, cache, rng)?;
+            // Salvo en && y ||, los booleanos se operan como 1 (verdadero) o 0 (falso).
+            let (left, right) = match op {
+                parser::BinaryOp::And | parser::BinaryOp::Or => (left, right),
+                _ => (left.into_numeric(), right.into_numeric()),
+            };
             match op {
                 parser::BinaryOp::Add => functions::add(&left, &right),
                 parser::BinaryOp::Subtract => functions::subtract(&left, &right),
@@ -188,108 +504,251 @@ fn evaluate_expression(expr: &AstNode, variables: &Variables) -> Result<Value, S
                 parser::BinaryOp::Divide => functions::divide(&left, &right),
                 parser::BinaryOp::RightDivide => functions::right_divide(&left, &right),
                 parser::BinaryOp::Power => functions::pow(&left, &right),
+                parser::BinaryOp::Less => functions::less(&left, &right),
+                parser::BinaryOp::Greater => functions::greater(&left, &right),
+                parser::BinaryOp::LessEqual => functions::less_equal(&left, &right),
+                parser::BinaryOp::GreaterEqual => functions::greater_equal(&left, &right),
+                parser::BinaryOp::Equal => functions::equal(&left, &right),
+                parser::BinaryOp::NotEqual => functions::not_equal(&left, &right),
+                parser::BinaryOp::And => functions::and(&left, &right),
+                parser::BinaryOp::Or => functions::or(&left, &right),
             }
         }
 
         // Se econtró una función. (Como sin(5), o det(A))
         // Todas las funciones se encuentran en functions/mod.rs
         AstNode::Call { func, args } => {
+            // Si es una función costosa aplicada directamente a una variable con una matriz,
+            // se intenta reutilizar el resultado guardado.
+            if let Some(result) = cached_call(func, args, variables, cache) {
+                return result;
+            }
+
             // Primero, se evalúa cada argumento de la función.
             let mut evaluated_args: Vec<Value> = Vec::new();
             for arg in args {
-                evaluated_args.push(evaluate_expression(arg, variables)?);
+                evaluated_args.push(evaluate_expression(arg, variables, cache, rng)?);
             }
 
-            let name = func.as_str();
+            // Si el nombre es el de una variable con una función anónima, se llama a esa.
+            if let Some(Value::Function(function)) = variables.get(func) {
+                return call_function(func, function, &evaluated_args, cache, rng);
+            }
 
-            // Se llama a la función correspondiente.
-            match name {
-                "abs" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función abs() recibe un argumento".to_string());
-                    }
-                    functions::abs(&evaluated_args[0])
-                }
-                "sqrt" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función sqrt() recibe un argumento".to_string());
-                    }
-                    functions::sqrt(&evaluated_args[0])
-                }
-                "pow" => {
-                    if evaluated_args.len() != 2 {
-                        return Err("La función pow() recibe dos argumentos".to_string());
-                    }
-                    functions::pow(&evaluated_args[0], &evaluated_args[1])
-                }
-                "inv" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función inv() recibe un argumento".to_string());
-                    }
-                    functions::inverse(&evaluated_args[0])
-                }
-                "factorial" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función factorial() recibe un argumento".to_string());
-                    }
-                    functions::factorial(&evaluated_args[0])
-                }
-                "sin" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función sin() recibe un argumento".to_string());
-                    }
-                    functions::sin(&evaluated_args[0])
-                }
-                "cos" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función cos() recibe un argumento".to_string());
-                    }
-                    functions::cos(&evaluated_args[0])
-                }
-                "tan" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función tan() recibe un argumento".to_string());
-                    }
-                    functions::tan(&evaluated_args[0])
-                }
-                "log" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función log() recibe un argumento".to_string());
-                    }
-                    functions::log(&evaluated_args[0])
-                }
-                "transpose" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función transpose() recibe un argumento".to_string());
-                    }
-                    functions::transpose(&evaluated_args[0])
-                }
-                "det" => {
-                    if evaluated_args.len() != 1 {
-                        return Err("La función det() recibe un argumento".to_string());
-                    }
-                    functions::det(&evaluated_args[0])
-                }
-                "linsolve" => {
-                    if evaluated_args.len() != 2 {
-                        return Err("La función linsolve() recibe dos argumentos".to_string());
-                    }
-                    functions::linsolve(&evaluated_args[0], &evaluated_args[1])
-                }
-                _ => Err(format!("La función {} no está definida", name)),
+            // Se busca la función en el registro (ver registry.rs) y se la llama.
+            match registry::get(func) {
+                Some(builtin) => registry::call(builtin, &evaluated_args, rng),
+                None => Err(EvalError::UndefinedFunction {
+                    name: func.to_string(),
+                    suggestion: closest_function(func).map(str::to_string),
+                }),
             }
         }
     }
 }
 
+/// Llama a la función anónima guardada en la variable `name`. El cuerpo se evalúa
+/// con las variables que capturó al definirse y con los parámetros reemplazados por
+/// los argumentos.
+fn call_function(
+    name: &str,
+    function: &Function,
+    args: &[Value],
+    cache: &mut Cache,
+    rng: &mut Rng,
+) -> Result<Value, EvalError> {
+    let count = function.params.len();
+    if args.len() != count {
+        return Err(EvalError::WrongArguments(format!(
+            "La función {}() recibe {}",
+            name,
+            registry::arity_description(count, count)
+        )));
+    }
+
+    let mut scope = function.captured.clone();
+    for (param, arg) in function.params.iter().zip(args) {
+        scope.insert(param.clone(), arg.clone());
+    }
+    evaluate_expression(&function.body, &scope, cache, rng)
+}
+
+/// Arma el mensaje de un error de sintáxis: la marca debajo de la posición del error
+/// y una explicación con la columna donde ocurrió.
+fn syntax_error_message(input: &str, error: &Error<Rule>) -> String {
+    let column = match error.line_col {
+        LineColLocation::Pos((_, column)) | LineColLocation::Span((_, column), _) => column,
+    };
+    let explanation = match &error.variant {
+        // Errores con un mensaje propio (ver parser/mod.rs)
+        ErrorVariant::CustomError { message } => message.clone(),
+        ErrorVariant::ParsingError { .. } => {
+            "verifique que la expresión esté bien escrita.".to_string()
+        }
+    };
+    format!(
+        "{}\nError de sintáxis en la columna {}: {}",
+        syntax_error_marker(input, &error.location),
+        column,
+        explanation
+    )
+}
+
+/// Marca con `^` la posición de un error de sintáxis, debajo de la entrada del usuario.
+/// Si la entrada es muy larga, solo se muestra un fragmento alrededor del error.
+fn syntax_error_marker(input: &str, location: &InputLocation) -> String {
+    // pest devuelve posiciones en bytes, pero se necesitan en caracteres.
+    let to_chars = |pos: usize| input[..pos].chars().count();
+    let (start, end) = match *location {
+        InputLocation::Pos(pos) => (to_chars(pos), to_chars(pos) + 1),
+        InputLocation::Span((start, end)) => {
+            (to_chars(start), to_chars(end).max(to_chars(start) + 1))
+        }
+    };
+
+    if end <= ERROR_CONTEXT {
+        // El error es visible en la línea que escribió el usuario (después del "> ").
+        return format!("  {}{}", " ".repeat(start), "^".repeat(end - start));
+    }
+
+    // Se muestra un fragmento de la entrada alrededor del error.
+    let from = start.saturating_sub(ERROR_CONTEXT / 2);
+    let snippet: String = input.chars().skip(from).take(ERROR_CONTEXT).collect();
+    let suffix = if from + ERROR_CONTEXT < input.chars().count() {
+        "..."
+    } else {
+        ""
+    };
+    let marks = (end - start).min(ERROR_CONTEXT - (start - from));
+    format!(
+        "  ...{}{}\n     {}{}",
+        snippet,
+        suffix,
+        " ".repeat(start - from),
+        "^".repeat(marks)
+    )
+}
+
+/// Evalúa `det(A)` o `inv(A)` usando la caché, siempre que `A` sea una variable
+/// que contiene una matriz. Devuelve `None` si la llamada no se puede cachear.
+fn cached_call(
+    func: &str,
+    args: &[AstNode],
+    variables: &Variables,
+    cache: &mut Cache,
+) -> Option<Result<Value, EvalError>> {
+    let name = match (func, args) {
+        ("det" | "inv", [AstNode::Ident(name)]) => name,
+        _ => return None,
+    };
+    let matrix = match variables.get(name) {
+        Some(Value::Matrix(matrix)) => matrix,
+        _ => return None,
+    };
+
+    let key = (func.to_string(), name.clone());
+    let hash = matrix.content_hash();
+    if let Some((cached_hash, value)) = cache.get(&key) {
+        if *cached_hash == hash {
+            return Some(Ok(value.clone()));
+        }
+    }
+
+    let value = Value::Matrix(matrix.clone());
+    let result = if func == "det" {
+        functions::det(&value)
+    } else {
+        functions::inverse(&value)
+    };
+    // Solo se guardan los resultados exitosos.
+    if let Ok(result) = &result {
+        cache.insert(key, (hash, result.clone()));
+    }
+    Some(result)
+}
+
+/// Devuelve el listado de las variables definidas, ordenadas por nombre.
+/// Si `detailed` es `true`, también se muestra el tipo y las dimensiones de cada una.
+fn list_variables(variables: &Variables, detailed: bool) -> String {
+    let mut names: Vec<&String> = variables.keys().collect();
+    names.sort();
+
+    if !detailed {
+        return names
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<&str>>()
+            .join("  ");
+    }
+
+    // Se calcula el ancho de la columna de nombres para que quede alineada.
+    let width = names
+        .iter()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    let mut lines = vec![format!("{:width$}   {:7}   {}", "Nombre", "Tipo", "Tamaño")];
+    for name in names {
+        let (kind, rows, cols) = match &variables[name] {
+            Value::Scalar(_) => ("Real", 1, 1),
+            Value::Matrix(m) => ("Matriz", m.rows(), m.cols()),
+            Value::Text(t) => ("Texto", 1, t.chars().count()),
+            Value::Bool(_) => ("Lógico", 1, 1),
+            Value::Function(_) => ("Función", 1, 1),
+        };
+        lines.push(format!("{:width$}   {:7}   {}x{}", name, kind, rows, cols));
+    }
+    lines.join("\n")
+}
+
+/// Borra todas las variables definidas. Las predefinidas (pi, e, inf y nan) vuelven a
+/// tener su valor original.
+fn clear_variables(variables: &mut Variables) {
+    variables.clear();
+    define_constants(variables);
+}
+
+/// Define (o vuelve a definir) las variables predefinidas.
+fn define_constants(variables: &mut Variables) {
+    for (name, value) in CONSTANTS {
+        variables.insert(name.to_string(), Value::Scalar(value));
+    }
+}
+
+/// Retorna `true` si `name` es una de las variables predefinidas (pi, e, inf y nan).
+fn is_constant(name: &str) -> bool {
+    CONSTANTS.iter().any(|(constant, _)| *constant == name)
+}
+
+/// Retorna `true` si la variable no se guarda con `save`: las constantes, que ya
+/// están definidas al cargar, y ans, que no se puede asignar.
+fn is_reserved(name: &str) -> bool {
+    is_constant(name) || name == "ans"
+}
+
 fn show_menu() {
-    println!(
+    output!(
         "
     Comandos     
 
-    ?, help    Mostrar comandos disponibles
-    clc        Limpia la consola
-    exit       Termina el programa
+    ?, help          Mostrar comandos disponibles
+    clc              Limpia la consola
+    format long      Muestra los números con 15 dígitos significativos
+    format short     Muestra los números con 4 decimales
+    format brackets  Muestra las matrices entre corchetes, como se escriben
+    format plain     Muestra las matrices sin corchetes
+    format           Vuelve al formato por defecto
+    diary f          Guarda la sesión en el archivo f
+    diary off        Deja de guardar la sesión
+    seed n           Fija la semilla de rand() para obtener siempre los mismos números
+    who              Lista las variables definidas
+    whos             Lista las variables con su tipo y tamaño
+    clear            Borra todas las variables (excepto las constantes)
+    clear x          Borra la variable x
+    save f           Guarda las variables en el archivo f
+    load f           Carga las variables del archivo f
+    exit             Termina el programa
 
 
     Operaciones                       
@@ -300,8 +759,12 @@ fn show_menu() {
     /                  División                                 
     \\                  División a la derecha (a/b = b\\a)      
     ^, pow(a, n)       Potenciación                             
-    !, factorial(n)    Factorial                                
-    ', transpose(A)    Traspuesta de una matriz                 
+    !, factorial(n)    Factorial (con la función gamma si n no es entero)
+    ', .', transpose(A)
+                       Traspuesta de una matriz
+    <, >, <=, >=       Comparaciones (elemento a elemento en matrices)
+    ==, ~=             Igualdad y desigualdad (con tolerancia)
+    &&, ||, ~          Y, o y negación lógicos
     abs(n)             Valor absoluto                           
     sqrt(n)            Raíz cuadrada                           
     inv(a)             Inverso (de un real o de una matriz)     
@@ -311,6 +774,430 @@ fn show_menu() {
     log(x)             Logarítmo natural                        
     det(A)             Determinante                             
     linsolve(A, b)     Resuelve un sistema de ecuaciones lineal 
+    eig(A)             Autovalores de una matriz simétrica
+    lu(A)              Factorización LU (muestra P y L, devuelve U)
+    norm(A), norm(A, p)
+                       Norma (euclídea o de Frobenius, o p = 1, 2, inf)
+    reshape(A, m, n)   Cambia la dimensión de una matriz a mxn (fila por fila)
+    kron(A, B)         Producto de Kronecker
+    adj(A)             Matriz adjunta (traspuesta de los cofactores)
+    minor(A, i, j)     Menor (i, j) de una matriz
+    rand(), rand(n), rand(m, n)
+                       Número o matriz aleatoria uniforme en [0, 1)
+    cond(A)            Número de condición (con la norma 2)
+    pinv(A)            Pseudoinversa de Moore-Penrose (rango completo)
+    size(A)            Dimensiones de A como [filas, columnas]
+    numel(A)           Cantidad de elementos de A
+    length(A)          Mayor dimensión de A (0 si está vacía)
+    isempty(A)         Si A no tiene elementos
+    isscalar(A)        Si A es un número (o de 1x1)
+    isvector(A)        Si A es de 1xN o de Nx1
+    expm(A)            Exponencial de una matriz
+    nchoosek(n, k)     Número combinatorio (n en k)
+    cbrt(x)            Raíz cúbica (real)
+    nthroot(x, n)      Raíz n-ésima (real)
+    sind(x)            Seno (en grados)
+    cosd(x)            Coseno (en grados)
+    tand(x)            Tangente (en grados)
+    isequal(A, B)      Devuelve si A y B tienen la misma dimensión y los mismos elementos
+    repmat(A, m, n)    Repite A en una grilla de mxn copias
+    fliplr(A)          Invierte el orden de las columnas de A
+    flipud(A)          Invierte el orden de las filas de A
+    rot90(A, k)        Rota A 90 grados en sentido antihorario k veces (por defecto, una)
+    tril(A, k)         Parte triangular inferior de A, desde la diagonal k (por defecto, 0)
+    triu(A, k)         Parte triangular superior de A, desde la diagonal k (por defecto, 0)
+    issymmetric(A)     Devuelve si A es una matriz simétrica
+    linsolve_verbose(A, b)
+                       Como linsolve, pero muestra la forma escalonada reducida de (A|b)
+    num2str(x)         Convierte un número en texto
+    mat2str(A)         Convierte una matriz en texto, escrita como [1, 2; 3, 4]
+    disp(x)            Muestra x sin el nombre de la variable
+    class(x)           Tipo de x (\"double\", \"char\" o \"logical\")
+    if(c, a, b)        a si la condición c es verdadera, b si no (sin cortocircuito)
+    clamp(x, lo, hi)   Restringe x al intervalo [lo, hi]
+    hypot(a, b)        sqrt(a^2 + b^2), sin desbordar con valores grandes
+    deg2rad(x)         Convierte x de grados a radianes
+    rad2deg(x)         Convierte x de radianes a grados
     "
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evalúa la expresión de una entrada de una sola sentencia, con solo las
+    /// variables predefinidas.
+    fn eval(source: &str) -> Result<Value, EvalError> {
+        let mut variables = HashMap::new();
+        define_constants(&mut variables);
+        eval_with(source, &variables)
+    }
+
+    /// Evalúa la expresión de una entrada de una sola sentencia con las variables dadas.
+    fn eval_with(source: &str, variables: &Variables) -> Result<Value, EvalError> {
+        let statements = parse(source).unwrap();
+        assert_eq!(statements.len(), 1);
+        let mut cache = HashMap::new();
+        evaluate_expression(&statements[0].expr, variables, &mut cache, &mut Rng::new(1))
+    }
+
+    /// Lee una entrada como `read_input`, tomando las líneas de `lines`. Devuelve la
+    /// entrada completa, o `None` si se canceló o no había más líneas.
+    fn read_lines(lines: &[&str]) -> Option<String> {
+        let mut lines = lines.iter();
+        let result = read_input(|_| match lines.next() {
+            Some(line) => ReadResult::Line(line.to_string()),
+            None => ReadResult::Eof,
+        });
+        match result {
+            ReadResult::Line(input) => Some(input),
+            _ => None,
+        }
+    }
+
+    /// Evalúa una expresión que debe dar un número real.
+    fn eval_scalar(source: &str) -> f64 {
+        match eval(source) {
+            Ok(Value::Scalar(x)) => x,
+            Ok(value) => panic!("{}: se esperaba un número, no {}", source, value),
+            Err(error) => panic!("{}: error inesperado: {}", source, error),
+        }
+    }
+
+    /// Evalúa una expresión que debe dar un booleano.
+    fn eval_bool(source: &str) -> bool {
+        match eval(source) {
+            Ok(Value::Bool(b)) => b,
+            Ok(value) => panic!("{}: se esperaba un booleano, no {}", source, value),
+            Err(error) => panic!("{}: error inesperado: {}", source, error),
+        }
+    }
+
+    #[test]
+    fn constants_cannot_be_reassigned_or_lost() {
+        assert!(check_assignable(Some("pi")).is_err());
+        assert!(check_assignable(Some("ans")).is_err());
+        assert!(check_assignable(Some("sin")).is_err());
+        assert!(check_assignable(Some("x")).is_ok());
+
+        let mut variables: Variables = HashMap::new();
+        variables.insert("x".to_string(), Value::Scalar(1.0));
+        clear_variables(&mut variables);
+        assert!(!variables.contains_key("x"));
+        assert!(matches!(variables["pi"], Value::Scalar(pi) if pi == std::f64::consts::PI));
+        assert_eq!(variables.len(), CONSTANTS.len());
+    }
+
+    #[test]
+    fn lists_variables_by_name() {
+        let mut variables: Variables = HashMap::new();
+        variables.insert("x".to_string(), Value::Scalar(1.0));
+        variables.insert("A".to_string(), Value::Matrix(Matrix::new(2, 3)));
+        variables.insert("nombre".to_string(), Value::Text("hola".to_string()));
+
+        assert_eq!(list_variables(&variables, false), "A  nombre  x");
+        assert_eq!(
+            list_variables(&variables, true),
+            "Nombre   Tipo      Tamaño\n\
+             A        Matriz    2x3\n\
+             nombre   Texto     1x4\n\
+             x        Real      1x1"
+        );
+        assert_eq!(list_variables(&HashMap::new(), false), "");
+    }
+
+    #[test]
+    fn matrix_elements_must_be_numbers() {
+        let error =
+            EvalError::TypeError("Los elementos de una matriz deben ser números".to_string());
+        assert_eq!(eval("[1, \"a\"]").err(), Some(error));
+        assert!(matches!(eval("[1, 2 > 1]"), Ok(Value::Matrix(_))));
+        assert!(matches!(eval("[1, [2, 3]]"), Err(EvalError::TypeError(_))));
+    }
+
+    #[test]
+    fn clear_keeps_constants() {
+        let mut variables: Variables = HashMap::new();
+        variables.insert("A".to_string(), Value::Matrix(Matrix::identity(2)));
+        variables.insert("ans".to_string(), Value::Scalar(3.0));
+        clear_variables(&mut variables);
+
+        let mut names: Vec<&str> = variables.keys().map(|name| name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["e", "inf", "nan", "pi"]);
+        assert!(matches!(variables["e"], Value::Scalar(e) if e == std::f64::consts::E));
+    }
+
+    #[test]
+    fn reassigning_a_variable_invalidates_its_cached_det() {
+        let mut variables: Variables = HashMap::new();
+        let mut cache = HashMap::new();
+        let det = parse("det(A)").unwrap().remove(0).expr;
+        let AstNode::Call { args, .. } = &det else {
+            panic!("se esperaba una llamada");
+        };
+
+        let a = Matrix::from_row_slice(2, 2, &[1.0, 2.0, 3.0, 4.0]).unwrap();
+        variables.insert("A".to_string(), Value::Matrix(a));
+        let first = cached_call("det", args, &variables, &mut cache);
+        assert!(matches!(first, Some(Ok(Value::Scalar(d))) if d == -2.0));
+        assert_eq!(cache.len(), 1);
+
+        variables.insert("A".to_string(), Value::Matrix(Matrix::identity(2)));
+        let second = cached_call("det", args, &variables, &mut cache);
+        assert!(matches!(second, Some(Ok(Value::Scalar(d))) if d == 1.0));
+
+        // Solo se cachean las llamadas a det e inv sobre una variable.
+        assert!(cached_call("det", &[AstNode::Scalar(2.0)], &variables, &mut cache).is_none());
+    }
+
+    #[test]
+    fn parses_large_matrix_literals() {
+        let row = vec!["1.5"; 100].join(", ");
+        let input = format!("[{}]", vec![row; 100].join("; "));
+        let Ok(Value::Matrix(a)) = eval(&input) else {
+            panic!("se esperaba una matriz");
+        };
+        assert_eq!((a.rows(), a.cols()), (100, 100));
+
+        // El error se marca en un fragmento acotado de la entrada.
+        let input = input.replacen("1.5", "1.5 +* 2", 5000);
+        let error = parse(&input).unwrap_err();
+        let message = syntax_error_message(&input, &error);
+        assert!(
+            message.lines().all(|line| line.chars().count() < 100),
+            "{}",
+            message
+        );
+        assert!(message.contains("Error de sintáxis en la columna"));
+    }
+
+    #[test]
+    fn logical_operators() {
+        for (a, b) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            assert_eq!(eval_bool(&format!("{} && {}", a, b)), a == 1 && b == 1);
+            assert_eq!(eval_bool(&format!("{} || {}", a, b)), a == 1 || b == 1);
+        }
+        assert!(eval_bool("~0"));
+        assert!(!eval_bool("~5"));
+        assert!(eval_bool("~(1 > 2)"));
+
+        // Los operadores lógicos tienen menor precedencia que las comparaciones, y
+        // && tiene mayor precedencia que ||.
+        assert!(!eval_bool("1 < 2 && 3 > 4"));
+        assert!(eval_bool("1 || 0 && 0"));
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_position() {
+        let input = "2 + ";
+        let error = parse(input).unwrap_err();
+        assert_eq!(
+            syntax_error_message(input, &error),
+            "      ^\nError de sintáxis en la columna 5: verifique que la expresión esté bien escrita."
+        );
+    }
+
+    #[test]
+    fn errors_have_specific_variants() {
+        assert_eq!(
+            eval("x + 1").err(),
+            Some(EvalError::UndefinedVariable("x".to_string()))
+        );
+        assert!(matches!(
+            eval("[1, 2] + [1, 2, 3]"),
+            Err(EvalError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            eval("sqrt(1, 2)"),
+            Err(EvalError::WrongArguments(_))
+        ));
+        assert!(matches!(
+            eval("factorial(\"a\")"),
+            Err(EvalError::TypeError(_))
+        ));
+        assert!(matches!(eval("sqrt(-1)"), Err(EvalError::DomainError(_))));
+        assert!(matches!(
+            eval("inv([1, 2; 2, 4])"),
+            Err(EvalError::Singular(_))
+        ));
+        assert!(matches!(
+            eval("foo(1)"),
+            Err(EvalError::UndefinedFunction { name, .. }) if name == "foo"
+        ));
+    }
+
+    #[test]
+    fn implicit_multiplication() {
+        assert_eq!(eval_scalar("2pi"), std::f64::consts::TAU);
+        assert_eq!(eval_scalar("2(3)"), 6.0);
+        assert_eq!(eval_scalar("3(1 + 1)^2"), 12.0);
+        assert_eq!(eval_scalar("sin(0)"), 0.0);
+        assert!(matches!(
+            parse("sin(0)").unwrap()[0].expr,
+            AstNode::Call { ref func, .. } if func == "sin"
+        ));
+    }
+
+    #[test]
+    fn infinity_and_nan_constants() {
+        assert_eq!(eval_scalar("inf"), f64::INFINITY);
+        assert_eq!(eval_scalar("-inf"), f64::NEG_INFINITY);
+        assert!(eval_scalar("nan").is_nan());
+        assert_eq!(
+            eval("[1, -inf, nan]").unwrap().to_string(),
+            "\n\n   1   -inf   nan\n"
+        );
+    }
+
+    #[test]
+    fn division_by_zero_follows_ieee() {
+        assert_eq!(eval_scalar("1/0"), f64::INFINITY);
+        assert_eq!(eval_scalar("-1/0"), f64::NEG_INFINITY);
+        assert!(eval_scalar("0/0").is_nan());
+        // La inversa de una matriz singular sigue siendo un error.
+        assert!(matches!(
+            eval("[1, 2] / [0, 0; 0, 0]"),
+            Err(EvalError::Singular(_))
+        ));
+    }
+
+    #[test]
+    fn empty_and_ragged_matrix_literals() {
+        assert!(matches!(eval("[]"), Ok(Value::Matrix(m)) if m.rows() == 0 && m.cols() == 0));
+        assert!(matches!(eval("[1, 2;]"), Ok(Value::Matrix(m)) if m.rows() == 1 && m.cols() == 2));
+        assert!(matches!(
+            eval("[1, 2; 3]"),
+            Err(EvalError::DimensionMismatch(_))
+        ));
+        assert!(matches!(eval("[[]]"), Err(EvalError::TypeError(_))));
+    }
+
+    #[test]
+    fn mat2str_round_trips_through_the_parser() {
+        for source in ["[1.5, -2; 1/3, 4e-20]", "[inf, -inf, 0]", "[]", "[-0.1]"] {
+            let original = eval(source).unwrap();
+            let Ok(Value::Text(text)) = functions::mat2str(&original) else {
+                panic!("se esperaba un texto");
+            };
+            let parsed = eval(&text).unwrap();
+            assert!(
+                matches!(
+                    functions::isequal(&original, &parsed),
+                    Ok(Value::Bool(true))
+                ),
+                "{} -> {}",
+                source,
+                text
+            );
+        }
+        assert!(matches!(eval("num2str(1/3)"), Ok(Value::Text(text)) if text == "0.3333"));
+    }
+
+    #[test]
+    fn suggests_the_closest_function() {
+        assert_eq!(closest_function("sine"), Some("sin"));
+        assert_eq!(closest_function("sqr"), Some("sqrt"));
+        assert_eq!(closest_function("linsolv"), Some("linsolve"));
+        assert_eq!(closest_function("foo"), None);
+        assert!(matches!(
+            eval("sine(0)"),
+            Err(EvalError::UndefinedFunction { suggestion: Some(s), .. }) if s == "sin"
+        ));
+    }
+
+    #[test]
+    fn anonymous_functions() {
+        let mut variables: Variables = HashMap::new();
+        variables.insert("f".to_string(), eval("@(x) x^2").unwrap());
+        variables.insert("a".to_string(), Value::Scalar(10.0));
+        variables.insert(
+            "g".to_string(),
+            eval_with("@(x, y) a*x + y", &variables).unwrap(),
+        );
+
+        assert!(matches!(eval_with("f(3)", &variables), Ok(Value::Scalar(x)) if x == 9.0));
+        assert!(matches!(eval_with("g(2, 1)", &variables), Ok(Value::Scalar(x)) if x == 21.0));
+        assert_eq!(
+            eval_with("f(1, 2)", &variables).err(),
+            Some(EvalError::WrongArguments(
+                "La función f() recibe un argumento".to_string()
+            ))
+        );
+
+        // Las variables se capturan al definir la función.
+        variables.insert("a".to_string(), Value::Scalar(0.0));
+        assert!(matches!(eval_with("g(2, 1)", &variables), Ok(Value::Scalar(x)) if x == 21.0));
+    }
+
+    #[test]
+    fn joins_continued_lines() {
+        assert_eq!(strip_continuation("1 + ... "), Some("1 + "));
+        assert_eq!(strip_continuation("1 + 2"), None);
+
+        assert_eq!(
+            read_lines(&["x = 1 + ...", "2"]).as_deref(),
+            Some("x = 1 +  2")
+        );
+        assert_eq!(
+            read_lines(&["a = 1 ...", "+ 2 ...", "+ 3", "b"]).as_deref(),
+            Some("a = 1  + 2  + 3")
+        );
+        // Los comentarios se descartan antes de unir.
+        assert_eq!(
+            read_lines(&["1 + ... % suma", "2"]).as_deref(),
+            Some("1 +  2")
+        );
+        // Si la entrada termina en medio de una continuación, se usa lo leído.
+        assert_eq!(read_lines(&["1 + ..."]).as_deref(), Some("1 + "));
+        assert_eq!(read_lines(&[]), None);
+    }
+
+    #[test]
+    fn detects_unclosed_brackets() {
+        assert_eq!(unclosed_brackets("[1, 2; 3, 4]"), []);
+        assert_eq!(unclosed_brackets("[1, 2;"), ['[']);
+        assert_eq!(unclosed_brackets("f([1, (2"), ['(', '[', '(']);
+        // Los corchetes dentro de textos y comentarios no cuentan.
+        assert_eq!(unclosed_brackets("disp(\"[\")"), []);
+        assert_eq!(unclosed_brackets("x = 1 % ["), []);
+        assert_eq!(unclosed_brackets("[\"\\\"[\""), ['[']);
+        // Los cierres de más se informan al parsear.
+        assert_eq!(unclosed_brackets("1)"), []);
+
+        // Dentro de una matriz, cada salto de línea separa filas.
+        assert_eq!(
+            read_lines(&["A = [1, 2", "3, 4]"]).as_deref(),
+            Some("A = [1, 2; 3, 4]")
+        );
+        assert_eq!(
+            read_lines(&["A = [", "1, 2;", "3, 4]"]).as_deref(),
+            Some("A = [ 1, 2; 3, 4]")
+        );
+        // Una línea vacía cancela la entrada.
+        assert_eq!(read_lines(&["A = [1, 2", "", "x"]), None);
+    }
+
+    #[test]
+    fn factorial_operator_and_function_agree() {
+        // `n!` y `factorial(n)` usan la misma implementación.
+        assert_eq!(eval_scalar("2.5!"), eval_scalar("factorial(2.5)"));
+        assert_eq!(eval_scalar("5!"), 120.0);
+        assert_eq!(eval("(-1)!").err(), eval("factorial(-1)").err());
+        assert!(matches!(eval("(-1)!"), Err(EvalError::DomainError(_))));
+    }
+
+    #[test]
+    fn end_is_an_ordinary_name_without_indexing() {
+        // Todavía no hay indexación, así que `end` no tiene un significado especial.
+        let mut variables: Variables = HashMap::new();
+        variables.insert("A".to_string(), Value::Matrix(Matrix::identity(2)));
+        assert_eq!(
+            eval_with("A(end)", &variables).err(),
+            Some(EvalError::UndefinedVariable("end".to_string()))
+        );
+        variables.insert("end".to_string(), Value::Scalar(2.0));
+        assert!(matches!(eval_with("end + 1", &variables), Ok(Value::Scalar(x)) if x == 3.0));
+    }
+}
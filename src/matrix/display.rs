@@ -2,34 +2,185 @@
 // Se encarga de que se vea lindo y bien justificado. Excede a la materia.
 
 use super::Matrix;
-use crate::utils::format_float;
+use crate::utils::{bracket_format, format_float, nearly_equal};
 use std::fmt;
 
 impl fmt::Display for Matrix {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut elements = vec![vec![String::new(); self.cols]; self.rows];
+        // Una matriz vacía se muestra como [].
+        if self.data.is_empty() {
+            return write!(f, "[]");
+        }
+        // Una matriz de 1x1 se muestra como un número, sin el bloque de filas.
+        if self.is_number() {
+            return write!(f, "{}", format_float(self.data[0]));
+        }
+
+        // Cada elemento se separa en su parte entera (con el signo) y su parte decimal
+        // (con el punto), para poder alinear los puntos decimales de cada columna.
+        let mut elements = vec![vec![(String::new(), String::new()); self.cols]; self.rows];
         for (row, col, val) in self {
-            elements[row][col] = format_float(val);
+            let text = format_float(snap(val));
+            elements[row][col] = match text.find('.') {
+                Some(dot) => (text[..dot].to_string(), text[dot..].to_string()),
+                None => (text, String::new()),
+            };
         }
 
-        let mut widths = vec![0; self.cols];
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                widths[col] = widths[col].max(elements[row][col].len());
+        // Ancho máximo de la parte entera y de la parte decimal de cada columna.
+        let mut int_widths = vec![0; self.cols];
+        let mut frac_widths = vec![0; self.cols];
+        for row in &elements {
+            for (col, (int, frac)) in row.iter().enumerate() {
+                int_widths[col] = int_widths[col].max(int.len());
+                frac_widths[col] = frac_widths[col].max(frac.len());
             }
         }
 
-        write!(f, "\n")?;
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                if col == 0 {
-                    write!(f, "\n")?;
-                }
-                let val = &elements[row][col];
-                let padding = 3 + widths[col] - val.len();
-                write!(f, "{}{}", " ".repeat(padding), val)?;
+        writeln!(f)?;
+        if bracket_format() {
+            return write_brackets(f, &elements, &int_widths, &frac_widths);
+        }
+        for row in &elements {
+            let mut line = String::new();
+            for (col, (int, frac)) in row.iter().enumerate() {
+                // La parte entera se alinea a la derecha y la decimal a la izquierda.
+                let left = 3 + int_widths[col] - int.len();
+                let right = frac_widths[col] - frac.len();
+                line.push_str(&format!(
+                    "{}{}{}{}",
+                    " ".repeat(left),
+                    int,
+                    frac,
+                    " ".repeat(right)
+                ));
             }
+            write!(f, "\n{}", line.trim_end())?;
         }
-        write!(f, "\n")
+        writeln!(f)
+    }
+}
+
+/// Redondea `val` al entero más cercano si solo difiere de él por errores de redondeo
+/// (como 0.9999999999999 o -1e-17 al calcular A * inv(A)): si es casi igual en
+/// términos relativos, o si la diferencia es menor que un épsilon absoluto muy chico.
+/// Se compara con cada elemento y no con el mayor de la matriz para no borrar la
+/// parte decimal de los elementos chicos.
+fn snap(val: f64) -> f64 {
+    const EPSILON: f64 = 1e-14;
+    let rounded = val.round();
+    if nearly_equal(val, rounded) || (val - rounded).abs() <= EPSILON {
+        // Se suma 0.0 para que -0 se muestre como 0.
+        rounded + 0.0
+    } else {
+        val
+    }
+}
+
+/// Escribe la matriz entre corchetes, con los elementos separados por "," y las filas
+/// por ";", como se escribiría en la entrada:
+///
+///    [ 1, 2.5000;
+///     -3, 4]
+fn write_brackets(
+    f: &mut fmt::Formatter<'_>,
+    elements: &[Vec<(String, String)>],
+    int_widths: &[usize],
+    frac_widths: &[usize],
+) -> fmt::Result {
+    for (row_index, row) in elements.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(col, (int, frac))| {
+                // La parte entera se alinea a la derecha y la decimal a la izquierda.
+                let left = int_widths[col] - int.len();
+                let right = frac_widths[col] - frac.len();
+                format!("{}{}{}{}", " ".repeat(left), int, frac, " ".repeat(right))
+            })
+            .collect();
+
+        let start = if row_index == 0 { "[" } else { " " };
+        let end = if row_index + 1 == elements.len() {
+            "]"
+        } else {
+            ";"
+        };
+        write!(f, "\n   {}{}{}", start, cells.join(", ").trim_end(), end)?;
+    }
+    writeln!(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::set_bracket_format;
+
+    #[test]
+    fn snaps_round_off_to_integers() {
+        assert_eq!(snap(0.9999999999999999), 1.0);
+        assert_eq!(snap(-1e-17), 0.0);
+        assert!(snap(-1e-17).is_sign_positive());
+        assert_eq!(snap(1e10 + 1e-6), 1e10);
+    }
+
+    #[test]
+    fn keeps_fractional_parts_of_small_entries() {
+        assert_eq!(snap(0.5), 0.5);
+        assert_eq!(snap(0.25), 0.25);
+        assert_eq!(snap(1e-9), 1e-9);
+
+        let a = Matrix::from_row_slice(2, 2, &[1e10, 0.5, 0.3, 2.0]).unwrap();
+        let text = a.to_string();
+        assert!(
+            text.contains("0.5000") && text.contains("0.3000"),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn elements_are_formatted_like_scalars() {
+        let a = Matrix::from_row_slice(1, 2, &[1.0 / 3.0, 2.0]).unwrap();
+        assert_eq!(a.to_string(), "\n\n   0.3333   2\n");
+        assert_eq!(format_float(1.0 / 3.0), "0.3333");
+        assert_eq!(format_float(2.0), "2");
+    }
+
+    #[test]
+    fn single_element_is_shown_as_a_number() {
+        assert_eq!(Matrix::from_scalar(-2.5).to_string(), "-2.5000");
+        let a = Matrix::from_row_slice(2, 2, &[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(a.to_string(), "\n\n   1   2\n   3   4\n");
+        assert_eq!(Matrix::new(0, 0).to_string(), "[]");
+    }
+
+    #[test]
+    fn aligns_columns_on_the_decimal_point() {
+        let v = Matrix::from_row_slice(4, 1, &[1.5, -120.0, 0.25, 3.125]).unwrap();
+        assert_eq!(
+            v.to_string(),
+            "\n\n      1.5000\n   -120\n      0.2500\n      3.1250\n"
+        );
+    }
+
+    #[test]
+    fn formats_with_brackets() {
+        let a = Matrix::from_row_slice(2, 2, &[1.0, 2.5, -3.0, 4.0]).unwrap();
+        set_bracket_format(true);
+        let text = a.to_string();
+        set_bracket_format(false);
+        assert_eq!(text, "\n\n   [ 1, 2.5000;\n    -3, 4]\n");
+    }
+
+    #[test]
+    fn product_with_inverse_prints_a_clean_identity() {
+        let a =
+            Matrix::from_row_slice(3, 3, &[0.1, 0.7, 0.3, 2.0, -1.3, 0.9, 4.1, 0.2, -5.0]).unwrap();
+        let product = Matrix::multiply(&a, &a.inverse().unwrap()).unwrap();
+        assert_eq!(
+            product.to_string(),
+            "\n\n   1   0   0\n   0   1   0\n   0   0   1\n"
+        );
     }
 }
@@ -9,7 +9,7 @@ impl<'a> IntoIterator for &'a Matrix {
 
     fn into_iter(self) -> Self::IntoIter {
         MatrixIntoIterator {
-            matrix: &self,
+            matrix: self,
             index: 0,
         }
     }
@@ -24,13 +24,10 @@ impl<'a> Iterator for MatrixIntoIterator<'a> {
     type Item = (usize, usize, MatrixItem);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let element = self.matrix.data.get(self.index);
-        if element.is_none() {
-            return None;
-        }
+        let element = self.matrix.data.get(self.index)?;
         let row = self.index / self.matrix.cols;
         let col = self.index % self.matrix.cols;
         self.index += 1;
-        Some((row, col, *element.unwrap()))
+        Some((row, col, *element))
     }
 }
@@ -8,6 +8,10 @@
 // - Obtención del determinante de una matriz
 
 use crate::utils::nearly_equal;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
 
 mod display;
 mod iter;
@@ -15,6 +19,16 @@ mod iter;
 /// Cada elemento de la matriz es un `double` (punto flotante de 64 bits)
 type MatrixItem = f64;
 
+/// Un pivote cuyo valor absoluto no supera esta fracción del mayor elemento de su
+/// fila en la matriz original se considera 0 al calcular el determinante.
+const PIVOT_TOLERANCE: MatrixItem = 1e-12;
+
+/// Cantidad de multiplicaciones (MxNxP) a partir de la cual el producto de matrices
+/// se calcula en paralelo. Para matrices chicas, repartir el trabajo cuesta más de lo
+/// que se gana.
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 64 * 64 * 64;
+
 /// Internamente, cada matriz se almacena como un vector de
 /// MxN elementos, donde M es el número de filas y N el número de columnas.
 #[derive(Debug, Clone)]
@@ -51,7 +65,12 @@ impl Matrix {
 
     /// Crea una matriz a partir de un vector de vectores. Útil cuando se
     /// quiere crear una matriz a partir de datos de entrada.
+    /// Las filas vacías se ignoran, por lo que `vec![vec![]]` es una matriz de 0x0.
     pub fn from_2d(nested_vec: Vec<Vec<MatrixItem>>) -> Result<Matrix, &'static str> {
+        let nested_vec: Vec<Vec<MatrixItem>> = nested_vec
+            .into_iter()
+            .filter(|row| !row.is_empty())
+            .collect();
         let rows = nested_vec.len();
         if rows == 0 {
             return Ok(Matrix {
@@ -65,7 +84,9 @@ impl Matrix {
         let mut matrix = Matrix::new(rows, cols);
         for (i, row) in nested_vec.iter().enumerate() {
             if row.len() != cols {
-                return Err("Todas las filas deben tener la misma cantidad de columnas");
+                return Err(
+                    "La matriz está mal declarada: el número de columnas no es consistente",
+                );
             }
 
             for (j, &val) in row.iter().enumerate() {
@@ -75,6 +96,23 @@ impl Matrix {
         Ok(matrix)
     }
 
+    /// Crea una matriz de MxN a partir de sus elementos, dados fila por fila.
+    pub fn from_row_slice(
+        rows: usize,
+        cols: usize,
+        data: &[MatrixItem],
+    ) -> Result<Matrix, &'static str> {
+        if data.len() != rows * cols {
+            return Err("La cantidad de elementos no coincide con la dimensión de la matriz");
+        }
+
+        Ok(Matrix {
+            rows,
+            cols,
+            data: data.to_vec(),
+        })
+    }
+
     /// Crea una matriz identidad de MxM elementos.
     pub fn identity(size: usize) -> Matrix {
         let mut matrix = Matrix::new(size, size);
@@ -86,19 +124,23 @@ impl Matrix {
 
     /// Suma dos matrices y retorna una nueva matriz.
     pub fn add(left: &Matrix, right: &Matrix) -> Result<Matrix, &'static str> {
-        if left.rows != right.rows || left.cols != right.cols {
+        let mut result = left.clone();
+        result.add_assign(right)?;
+        Ok(result)
+    }
+
+    /// Le suma otra matriz a la matriz, sin crear una nueva.
+    /// Si las dimensiones no coinciden, la matriz no se modifica.
+    pub fn add_assign(&mut self, other: &Matrix) -> Result<(), &'static str> {
+        if self.rows != other.rows || self.cols != other.cols {
             return Err("La suma de matrices solo está definida para matrices de igual dimensión");
         }
 
-        let mut result = Matrix::new(left.rows, left.cols);
-        for i in 0..result.rows {
-            for j in 0..result.cols {
-                // Esto es Aij + Bij = Cij
-                let val = left.get(i, j)? + right.get(i, j)?;
-                result.set(i, j, val)?;
-            }
+        for (a, b) in self.data.iter_mut().zip(&other.data) {
+            // Esto es Aij + Bij
+            *a += b;
         }
-        Ok(result)
+        Ok(())
     }
 
     /// Multiplica dos matrices (MxN y NxP) y retorna una nueva matriz (MxP).
@@ -112,24 +154,62 @@ impl Matrix {
         // El resultado de la multiplicación de matrices es una matriz MxP.
         let mut result = Matrix::new(left.rows, right.cols);
 
-        for m in 0..result.rows {
-            for p in 0..result.cols {
-                // Inicializa el elemento Cmp en 0.
-                let mut sum: f64 = 0.0;
+        // Los elementos se guardan fila por fila, así que recorrer una columna de la
+        // matriz derecha salta por la memoria (y es lento para matrices grandes).
+        // Trasponiéndola una sola vez, sus columnas pasan a ser filas contiguas.
+        let right_t = right.transpose();
+        let n = left.cols;
 
-                // Suma de los productos de los elementos de la fila i de la matriz izquierda
-                // con los elementos de la columna j de la matriz derecha.
-                for n in 0..left.cols {
-                    // Esto es Amn * Bnp
-                    sum += left.get(m, n)? * right.get(n, p)?;
-                }
-                result.set(m, p, sum)?;
+        // Calcula la fila m del resultado.
+        let compute_row = |(m, row): (usize, &mut [MatrixItem])| {
+            let left_row = &left.data[m * n..(m + 1) * n];
+            for (p, val) in row.iter_mut().enumerate() {
+                let col = &right_t.data[p * n..(p + 1) * n];
+                // Suma de los productos de los elementos de la fila m de la matriz izquierda
+                // con los elementos de la columna p de la matriz derecha.
+                // Esto es Cmp = Σ Amn * Bnp
+                *val = left_row.iter().zip(col).map(|(a, b)| a * b).sum();
             }
+        };
+
+        // Con la feature `parallel`, las filas de las matrices grandes se reparten entre
+        // varios hilos. Cada fila se calcula igual que en serie, así que el resultado es
+        // idéntico.
+        #[cfg(feature = "parallel")]
+        if result.rows * result.cols * n >= PARALLEL_THRESHOLD {
+            use rayon::prelude::*;
+            result
+                .data
+                .par_chunks_mut(result.cols.max(1))
+                .enumerate()
+                .for_each(compute_row);
+            return Ok(result);
         }
 
+        result
+            .data
+            .chunks_mut(result.cols.max(1))
+            .enumerate()
+            .for_each(compute_row);
+
         Ok(result)
     }
 
+    /// Calcula el producto de Kronecker de dos matrices (MxN y PxQ) y retorna una
+    /// nueva matriz (MPxNQ), formada por los bloques Aij * B.
+    pub fn kron(left: &Matrix, right: &Matrix) -> Matrix {
+        let mut result = Matrix::new(left.rows * right.rows, left.cols * right.cols);
+        for (i, j, a) in left {
+            // El bloque (i, j) empieza en la fila i*P y la columna j*Q.
+            for (k, l, b) in right {
+                result
+                    .set(i * right.rows + k, j * right.cols + l, a * b)
+                    .unwrap();
+            }
+        }
+        result
+    }
+
     // --------------------
     // Métodos de instancia
     // --------------------
@@ -172,6 +252,11 @@ impl Matrix {
         self.rows == self.cols
     }
 
+    /// Retorna `true` si la matriz es de 1x1, es decir, si representa un número.
+    pub fn is_number(&self) -> bool {
+        self.rows == 1 && self.cols == 1
+    }
+
     /// Retorna `true` si la matriz es una matriz identidad.
     pub fn is_identity(&self) -> bool {
         if !self.is_square() {
@@ -181,15 +266,84 @@ impl Matrix {
         // Recorre la matriz y verifica que todos los elementos
         // en la diagonal principal sean 1 y los demás sean 0.
         for (i, j, val) in self {
-            if i == j && !nearly_equal(val, 1.0) {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            if !nearly_equal(val, expected) {
                 return false;
-            } else if i != j && !nearly_equal(val, 0.0) {
+            }
+        }
+        true
+    }
+
+    /// Retorna `true` si la matriz es cuadrada y todos los elementos fuera de la
+    /// diagonal principal son 0.
+    pub fn is_diagonal(&self) -> bool {
+        self.is_square()
+            && self
+                .into_iter()
+                .all(|(i, j, val)| i == j || nearly_equal(val, 0.0))
+    }
+
+    /// Retorna `true` si la matriz es cuadrada y todos los elementos debajo de la
+    /// diagonal principal son 0.
+    pub fn is_upper_triangular(&self) -> bool {
+        self.is_square()
+            && self
+                .into_iter()
+                .all(|(i, j, val)| j >= i || nearly_equal(val, 0.0))
+    }
+
+    /// Retorna `true` si la matriz es cuadrada y todos los elementos encima de la
+    /// diagonal principal son 0.
+    pub fn is_lower_triangular(&self) -> bool {
+        self.is_square()
+            && self
+                .into_iter()
+                .all(|(i, j, val)| j <= i || nearly_equal(val, 0.0))
+    }
+
+    /// Retorna `true` si la matriz es cuadrada y es igual a su transpuesta.
+    pub fn is_symmetric(&self) -> bool {
+        if !self.is_square() {
+            return false;
+        }
+
+        // Alcanza con comparar los elementos debajo de la diagonal con sus opuestos.
+        for (i, j, val) in self {
+            if j < i && !nearly_equal(val, self.get(j, i).unwrap()) {
                 return false;
             }
         }
         true
     }
 
+    /// Calcula un hash del contenido de la matriz (dimensiones y elementos).
+    /// Dos matrices con el mismo contenido tienen el mismo hash.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.rows.hash(&mut hasher);
+        self.cols.hash(&mut hasher);
+        for val in &self.data {
+            val.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Busca, entre las filas `from` a M-1, la fila cuyo elemento en la columna `col`
+    /// tiene el mayor valor absoluto. Se usa para elegir el pivote (pivoteo parcial).
+    fn pivot_row(&self, from: usize, col: usize) -> Result<usize, &'static str> {
+        if from >= self.rows || col >= self.cols {
+            return Err("Índice fuera de rango");
+        }
+
+        let mut best = from;
+        for i in (from + 1)..self.rows {
+            if self.get(i, col)?.abs() > self.get(best, col)?.abs() {
+                best = i;
+            }
+        }
+        Ok(best)
+    }
+
     /// Operación elemental de tipo I.
     /// Permuta las filas `i` y `j`.
     pub fn swap_rows(&mut self, i: usize, j: usize) -> Result<(), &'static str> {
@@ -236,15 +390,43 @@ impl Matrix {
     /// Calcula la potencia de una matriz cuadrada. Retorna una nueva matriz.
     pub fn pow(&self, exp: f64) -> Result<Matrix, String> {
         if !self.is_square() {
-            return Err("La potencia solo está definida para matrices cuadradas".to_string());
+            return Err(format!(
+                "La potencia A^n es el producto A*A*...*A, por lo que solo está definida para matrices cuadradas (esta es de {}x{})",
+                self.rows, self.cols
+            ));
+        }
+
+        // Si la matriz es diagonal, alcanza con elevar cada elemento de la diagonal.
+        // Si alguno no tiene potencia (como 0 con exponente negativo), se sigue por el
+        // camino general para que devuelva el error que corresponde.
+        if self.is_diagonal() {
+            let mut result = Matrix::new(self.rows, self.cols);
+            for i in 0..self.rows {
+                result.set(i, i, self.get(i, i)?.powf(exp))?;
+            }
+            if result.data.iter().all(|val| val.is_finite()) {
+                return Ok(result);
+            }
         }
         if !nearly_equal(exp.fract(), 0.0) {
-            return Err("La potencia solo está definida para exponentes enteros".to_string());
+            // Para exponentes no enteros se diagonaliza la matriz, lo que por ahora
+            // solo está implementado para matrices simétricas.
+            if !self.is_symmetric() {
+                return Err(
+                    "Las potencias con exponente no entero solo están implementadas para matrices simétricas (los exponentes enteros, incluso negativos, funcionan con cualquier matriz cuadrada)"
+                        .to_string(),
+                );
+            }
+            return self.pow_spd(exp);
         }
 
         // Si el exponente es negativo, calcula la inversa de la matriz.
+        // Como la matriz es cuadrada, la inversa solo falla si la matriz es singular.
         let base = if exp < 0.0 {
-            self.inverse()?
+            self.inverse().map_err(|_| {
+                "La matriz no se puede elevar a un exponente negativo porque no tiene inversa (su determinante es 0)"
+                    .to_string()
+            })?
         } else {
             self.clone()
         };
@@ -271,13 +453,54 @@ impl Matrix {
 
     /// Multiplica la matriz por un escalar y retorna una nueva matriz.
     pub fn scale(&self, scalar: MatrixItem) -> Matrix {
-        let mut result = Matrix::new(self.rows, self.cols);
-        for (i, j, val) in self {
+        let mut result = self.clone();
+        result.scale_in_place(scalar);
+        result
+    }
+
+    /// Multiplica la matriz por un escalar, sin crear una nueva.
+    pub fn scale_in_place(&mut self, scalar: MatrixItem) {
+        for val in self.data.iter_mut() {
             // Multiplica cada elemento de la matriz por el escalar.
-            result.set(i, j, val * scalar).unwrap();
+            *val *= scalar;
+        }
+    }
+
+    /// Aplica la función `f` a cada elemento de la matriz y retorna una nueva matriz
+    /// de la misma dimensión.
+    pub fn map(&self, f: impl Fn(MatrixItem) -> MatrixItem) -> Matrix {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|&val| f(val)).collect(),
         }
-        result
     }
+
+    /// Aplica la función `f` a cada par de elementos correspondientes de dos matrices
+    /// de igual dimensión y retorna una nueva matriz.
+    pub fn zip_with(
+        left: &Matrix,
+        right: &Matrix,
+        f: impl Fn(MatrixItem, MatrixItem) -> MatrixItem,
+    ) -> Result<Matrix, &'static str> {
+        if left.rows != right.rows || left.cols != right.cols {
+            return Err(
+                "Las operaciones elemento a elemento solo están definidas para matrices de igual dimensión",
+            );
+        }
+
+        Ok(Matrix {
+            rows: left.rows,
+            cols: left.cols,
+            data: left
+                .data
+                .iter()
+                .zip(&right.data)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+        })
+    }
+
     /// Calcula y retorna el determinante de la matriz.
     /// Se calcula mediante eliminación gaussiana en vez de por
     /// expansión de cofactores debido a su eficiencia.
@@ -287,16 +510,36 @@ impl Matrix {
             return Err("El determinante solo está definida para matrices cuadradas.");
         }
 
+        // Si la matriz es diagonal, el determinante es el producto de la diagonal.
+        if self.is_diagonal() {
+            return Ok((0..self.rows).map(|i| self.get(i, i).unwrap()).product());
+        }
+
+        // Para una matriz de 2x2 alcanza con la fórmula ad - bc. (Las de 1x1 son
+        // diagonales, así que ya se resolvieron arriba.)
+        if self.rows == 2 {
+            let [a, b, c, d] = [self.data[0], self.data[1], self.data[2], self.data[3]];
+            // Si ad y bc solo difieren por redondeo, la matriz es singular.
+            if nearly_equal(a * d, b * c) {
+                return Ok(0.0);
+            }
+            return Ok(a * d - b * c);
+        }
+
         // Clono la matriz para no modificar la original
         let mut matrix = self.clone();
         let n = matrix.rows; // número de filas y columnas
+                             // Escala de cada fila, que se permuta junto con ellas.
+        let mut scales = self.row_scales();
         let mut determinant: f64 = 1.0;
 
         // Recorro la diagonal.
         // Como la matriz es cuadrada, me basta con un único índice que va desde 0 a n-1.
         //
-        // La estrategia es buscar la primera fila tal que Aik != 0 e intercambiarla con la fila k.
-        // Si no existe tal fila, el determinante es 0.
+        // La estrategia es buscar la fila i >= k tal que |Aik| sea el mayor posible (pivoteo
+        // parcial) e intercambiarla con la fila k. Elegir el pivote más grande, en vez del
+        // primero distinto de 0, reduce los errores de redondeo.
+        // Si todos los Aik son 0, el determinante es 0.
         //
         // Una vez intercambiada, se resta a cada fila i > k la fila k multiplicada por Aik/Akk.
         // Así, los elementos de la columna k quedan en 0 para esas filas.
@@ -304,29 +547,19 @@ impl Matrix {
         // Todo esto para que quede una matriz triangular superior. Así, el determinante es el
         // producto de los elementos de la diagonal.
         for k in 0..n {
-            // Obtengo el elemento de la diagonal (Akk, que será el pivote)
-            let mut pivot = matrix.get(k, k).unwrap();
-            if nearly_equal(pivot, 0.0) {
-                // Busco la primera fila tal que Aik != 0
-                let mut found = false;
-                let mut i = k + 1;
-                while !found && i < n {
-                    pivot = matrix.get(i, k).unwrap();
-                    if nearly_equal(pivot, 0.0) {
-                        i += 1;
-                    } else {
-                        found = true;
-                    }
-                }
-                if !found {
-                    // No existe tal fila, el determinante es 0
-                    return Ok(0.0);
-                } else {
-                    // Permuto la fila k con la fila i.
-                    matrix.swap_rows(k, i)?;
-                    // Cambio el signo del determinante, ya que det(E tipo I) = -1
-                    determinant = -determinant;
-                }
+            // Busco la fila con el mayor |Aik| (que será el pivote)
+            let i = matrix.pivot_row(k, k)?;
+            let pivot = matrix.get(i, k)?;
+            if Matrix::is_negligible_pivot(pivot, scales[i]) {
+                // No existe tal fila, el determinante es 0
+                return Ok(0.0);
+            }
+            if i != k {
+                // Permuto la fila k con la fila i.
+                matrix.swap_rows(k, i)?;
+                scales.swap(k, i);
+                // Cambio el signo del determinante, ya que det(E tipo I) = -1
+                determinant = -determinant;
             }
 
             // Ahora, toca restar a cada fila i > k la fila k multiplicada por Aik/Akk
@@ -358,6 +591,29 @@ impl Matrix {
         Ok(determinant)
     }
 
+    /// Retorna `true` si el pivote es despreciable frente a `scale`, el mayor elemento
+    /// (en valor absoluto) de su fila en la matriz original. Por errores de redondeo,
+    /// al eliminar una matriz singular el último pivote puede dar un número muy chico
+    /// en vez de 0.
+    /// Se compara con los elementos y no con el determinante porque una matriz
+    /// invertible pero mal condicionada (como la de Hilbert) también puede tener un
+    /// determinante muy chico. Y se compara con la fila y no con toda la matriz para
+    /// que un único elemento muy grande no haga despreciables a los pivotes del resto.
+    fn is_negligible_pivot(pivot: MatrixItem, scale: MatrixItem) -> bool {
+        pivot.abs() <= PIVOT_TOLERANCE * scale
+    }
+
+    /// Retorna, para cada fila, su mayor elemento en valor absoluto.
+    fn row_scales(&self) -> Vec<MatrixItem> {
+        (0..self.rows)
+            .map(|i| {
+                self.data[i * self.cols..(i + 1) * self.cols]
+                    .iter()
+                    .fold(0.0, |max: MatrixItem, val| max.max(val.abs()))
+            })
+            .collect()
+    }
+
     /// Retorna la inversa de la matriz.
     /// Se calcula obteniendo la forma escalonada reducida de Gauss-Jordan.
     pub fn inverse(&self) -> Result<Matrix, String> {
@@ -374,6 +630,18 @@ impl Matrix {
             return Err("La matriz no tiene inversa porque su determinante es 0".to_string());
         }
 
+        // Para matrices de 1x1 y 2x2 se usa directamente la fórmula A⁻¹ = adj(A) / det(A).
+        match self.rows {
+            1 => return Ok(Matrix::from_scalar(1.0 / determinant)),
+            2 => {
+                let [a, b, c, d] = [self.data[0], self.data[1], self.data[2], self.data[3]];
+                let mut result = Matrix::from_row_slice(2, 2, &[d, -b, -c, a])?;
+                result.scale_in_place(1.0 / determinant);
+                return Ok(result);
+            }
+            _ => {}
+        }
+
         // número de filas y columnas
         let n = self.rows;
         // clono la matriz para no modificar la original
@@ -384,8 +652,9 @@ impl Matrix {
         // Recorro la diagonal.
         // Como la matriz es cuadrada, me basta con un único índice que va desde 0 a n-1.
         //
-        // La estrategia es buscar la primera fila tal que Aik != 0 e intercambiarla con la fila k.
-        // Si no existe tal fila, la matriz no tiene inversa.
+        // La estrategia es buscar la fila i >= k tal que |Aik| sea el mayor posible (pivoteo
+        // parcial) e intercambiarla con la fila k. Si todos los Aik son 0, la matriz no
+        // tiene inversa.
         //
         // Una vez intercambiada, se divide cada elemento de la fila k por Akk. Así, Akk = 1.
         // Luego, se resta a cada fila i != k la fila k multiplicada por Aik. Así, los elementos
@@ -396,33 +665,20 @@ impl Matrix {
         // multiplicando por la inversa de la matriz original. Finalmente, la matriz
         // acumuladora será la inversa de la matriz original.
         for k in 0..n {
-            // Obtengo el elemento de la diagonal (Akk, que será el pivote)
-            let mut pivot = matrix.get(k, k).unwrap();
+            // Busco la fila con el mayor |Aik| (que será el pivote)
+            let i = matrix.pivot_row(k, k)?;
+            let pivot = matrix.get(i, k)?;
             if nearly_equal(pivot, 0.0) {
-                // Busco la primera fila tal que Aik != 0
-                let mut found = false;
-                // Solo busco en las filas k+1 a n-1, ya que las filas anteriores ya están en 0
-                let mut i = k + 1;
-                while !found && i < n {
-                    pivot = matrix.get(i, k).unwrap();
-                    if nearly_equal(pivot, 0.0) {
-                        i += 1;
-                    } else {
-                        found = true;
-                    }
-                }
-                if !found {
-                    // Nota: este mensaje no se debería mostrar nunca, ya que el determinante
-                    // debería ser 0. Como nadie quiere un bucle infinito, lo dejo por las dudas.
-                    return Err(
-                        "La matriz tiene una columna de ceros, por ende, no tiene inversa"
-                            .to_string(),
-                    );
-                } else {
-                    // Permuto la fila k con la fila i.
-                    matrix.swap_rows(k, i)?;
-                    accum.swap_rows(k, i)?;
-                }
+                // Nota: este mensaje no se debería mostrar nunca, ya que el determinante
+                // debería ser 0. Como nadie quiere un bucle infinito, lo dejo por las dudas.
+                return Err(
+                    "La matriz tiene una columna de ceros, por ende, no tiene inversa".to_string(),
+                );
+            }
+            if i != k {
+                // Permuto la fila k con la fila i.
+                matrix.swap_rows(k, i)?;
+                accum.swap_rows(k, i)?;
             }
 
             // Divido la fila k por Akk, así Akk = 1
@@ -447,4 +703,933 @@ impl Matrix {
         // Finalmente, retorno la matriz acumuladora
         Ok(accum)
     }
+
+    /// Retorna una matriz de MxN con los mismos elementos, tomados fila por fila
+    /// (el mismo orden en el que se guardan internamente). M*N debe ser igual a la
+    /// cantidad de elementos de la matriz.
+    pub fn reshape(&self, rows: usize, cols: usize) -> Result<Matrix, &'static str> {
+        // Si MxN desborda, tampoco puede coincidir con la cantidad de elementos.
+        if rows.checked_mul(cols) != Some(self.data.len()) {
+            return Err(
+                "La nueva dimensión debe tener la misma cantidad de elementos que la matriz",
+            );
+        }
+
+        Ok(Matrix {
+            rows,
+            cols,
+            data: self.data.clone(),
+        })
+    }
+
+    /// Retorna una matriz formada por MxN copias de la matriz, dispuestas en una
+    /// grilla de `m` filas y `n` columnas.
+    pub fn repmat(&self, m: usize, n: usize) -> Matrix {
+        let mut result = Matrix::new(self.rows * m, self.cols * n);
+        for (i, j, val) in self {
+            // La copia (p, q) empieza en la fila p*rows y la columna q*cols.
+            for p in 0..m {
+                for q in 0..n {
+                    result
+                        .set(p * self.rows + i, q * self.cols + j, val)
+                        .unwrap();
+                }
+            }
+        }
+        result
+    }
+
+    /// Retorna una matriz con las columnas en orden inverso (espejo horizontal).
+    pub fn fliplr(&self) -> Matrix {
+        let mut result = Matrix::new(self.rows, self.cols);
+        for (i, j, val) in self {
+            result.set(i, self.cols - 1 - j, val).unwrap();
+        }
+        result
+    }
+
+    /// Retorna una matriz con las filas en orden inverso (espejo vertical).
+    pub fn flipud(&self) -> Matrix {
+        let mut result = Matrix::new(self.rows, self.cols);
+        for (i, j, val) in self {
+            result.set(self.rows - 1 - i, j, val).unwrap();
+        }
+        result
+    }
+
+    /// Retorna la matriz rotada 90 grados en sentido antihorario `times` veces.
+    /// Si `times` es negativo, se rota en sentido horario.
+    pub fn rot90(&self, times: i64) -> Matrix {
+        match times.rem_euclid(4) {
+            0 => self.clone(),
+            // Rotar 180 grados es invertir tanto filas como columnas
+            2 => self.fliplr().flipud(),
+            quarter => {
+                let mut result = Matrix::new(self.cols, self.rows);
+                for (i, j, val) in self {
+                    if quarter == 1 {
+                        // La última columna pasa a ser la primera fila
+                        result.set(self.cols - 1 - j, i, val).unwrap();
+                    } else {
+                        // La última fila pasa a ser la primera columna
+                        result.set(j, self.rows - 1 - i, val).unwrap();
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Retorna la parte triangular inferior de la matriz: los elementos sobre o
+    /// debajo de la diagonal `k` (0 es la diagonal principal, positivos hacia arriba
+    /// y negativos hacia abajo). El resto se reemplaza por 0.
+    pub fn tril(&self, k: i64) -> Matrix {
+        let mut result = Matrix::new(self.rows, self.cols);
+        for (i, j, val) in self {
+            if (j as i64) - (i as i64) <= k {
+                result.set(i, j, val).unwrap();
+            }
+        }
+        result
+    }
+
+    /// Retorna la parte triangular superior de la matriz: los elementos sobre o
+    /// encima de la diagonal `k`. El resto se reemplaza por 0.
+    pub fn triu(&self, k: i64) -> Matrix {
+        let mut result = Matrix::new(self.rows, self.cols);
+        for (i, j, val) in self {
+            if (j as i64) - (i as i64) >= k {
+                result.set(i, j, val).unwrap();
+            }
+        }
+        result
+    }
+
+    /// Retorna la submatriz que se obtiene al eliminar la fila `row` y la columna `col`.
+    pub fn submatrix_excluding(&self, row: usize, col: usize) -> Result<Matrix, &'static str> {
+        if row >= self.rows || col >= self.cols {
+            return Err("Índice fuera de rango");
+        }
+
+        let mut result = Matrix::new(self.rows - 1, self.cols - 1);
+        for (i, j, val) in self {
+            if i == row || j == col {
+                continue;
+            }
+            // Los elementos que están después de la fila/columna eliminada se corren uno.
+            let new_i = if i > row { i - 1 } else { i };
+            let new_j = if j > col { j - 1 } else { j };
+            result.set(new_i, new_j, val)?;
+        }
+        Ok(result)
+    }
+
+    /// Retorna la matriz de cofactores, donde cada elemento es
+    /// Cij = (-1)^(i+j) * Mij, siendo Mij el menor (i, j).
+    pub fn cofactor_matrix(&self) -> Result<Matrix, &'static str> {
+        if !self.is_square() {
+            return Err("La matriz de cofactores solo está definida para matrices cuadradas");
+        }
+
+        // El único cofactor de una matriz de 1x1 es el determinante de la matriz vacía, 1.
+        if self.rows == 1 {
+            return Ok(Matrix::from_scalar(1.0));
+        }
+
+        let mut result = Matrix::new(self.rows, self.cols);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                let minor = self.submatrix_excluding(i, j)?.determinant()?;
+                let sign = if (i + j) % 2 == 0 { 1.0 } else { -1.0 };
+                result.set(i, j, sign * minor)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Retorna la matriz adjunta, que es la traspuesta de la matriz de cofactores.
+    /// Cumple que A * adj(A) = det(A) * I.
+    pub fn adjugate(&self) -> Result<Matrix, &'static str> {
+        Ok(self.cofactor_matrix()?.transpose())
+    }
+
+    /// Calcula la norma de Frobenius de la matriz, es decir, la raíz cuadrada
+    /// de la suma de los cuadrados de todos sus elementos.
+    pub fn frobenius_norm(&self) -> MatrixItem {
+        self.data
+            .iter()
+            .map(|val| val * val)
+            .sum::<MatrixItem>()
+            .sqrt()
+    }
+
+    /// Calcula la norma 1 inducida de la matriz: la máxima suma de los valores
+    /// absolutos de una columna.
+    pub fn norm_1(&self) -> MatrixItem {
+        (0..self.cols)
+            .map(|j| (0..self.rows).map(|i| self.get(i, j).unwrap().abs()).sum())
+            .fold(0.0, MatrixItem::max)
+    }
+
+    /// Calcula la norma infinito inducida de la matriz: la máxima suma de los
+    /// valores absolutos de una fila.
+    pub fn norm_inf(&self) -> MatrixItem {
+        (0..self.rows)
+            .map(|i| (0..self.cols).map(|j| self.get(i, j).unwrap().abs()).sum())
+            .fold(0.0, MatrixItem::max)
+    }
+
+    /// Calcula la exponencial de la matriz, eᴬ = I + A + A²/2! + A³/3! + ...
+    /// Se usa el método de escalado y elevación al cuadrado: como eᴬ = (e^(A/2ˢ))^(2ˢ),
+    /// se elige s para que A/2ˢ tenga norma chica (donde la serie converge rápido),
+    /// se suma la serie truncada y luego se eleva al cuadrado s veces.
+    pub fn expm(&self) -> Result<Matrix, &'static str> {
+        if !self.is_square() {
+            return Err("La exponencial solo está definida para matrices cuadradas");
+        }
+        // Con un elemento infinito, la cantidad de veces que hay que elevar al cuadrado
+        // también sería infinita.
+        if self.data.iter().any(|val| !val.is_finite()) {
+            return Err("La exponencial solo está definida para matrices de elementos finitos");
+        }
+
+        // Cantidad máxima de términos de la serie. Con norma <= 1/2 alcanzan de sobra.
+        const MAX_TERMS: usize = 30;
+
+        let norm = self.norm_inf();
+        let squarings = if norm > 0.5 {
+            (norm / 0.5).log2().ceil() as i32
+        } else {
+            0
+        };
+        let scaled = self.scale(0.5_f64.powi(squarings));
+
+        // Cada término es el anterior por A/k, así que no hace falta calcular factoriales.
+        let mut result = Matrix::identity(self.rows);
+        let mut term = Matrix::identity(self.rows);
+        for k in 1..=MAX_TERMS {
+            term = Matrix::multiply(&term, &scaled)?;
+            term.scale_in_place(1.0 / k as MatrixItem);
+            result.add_assign(&term)?;
+            if term.norm_inf() <= MatrixItem::EPSILON * result.norm_inf() {
+                break;
+            }
+        }
+
+        for _ in 0..squarings {
+            result = Matrix::multiply(&result, &result)?;
+        }
+        Ok(result)
+    }
+
+    /// Calcula y retorna la forma escalonada reducida de Gauss-Jordan de la matriz.
+    pub fn rref(&self) -> Result<Matrix, &'static str> {
+        // Clono la matriz para no modificar la original
+        let mut matrix = self.clone();
+
+        // Recorro la diagonal con un i y un j.
+        // El i es el índice de la fila y el j el de la columna.
+        //
+        // La estrategia es buscar la primera fila tal que Akj != 0 e intercambiarla con la fila i.
+        // Si no existe tal fila, se avanza a la columna siguiente y se repite el proceso.
+        //
+        // Una vez encontrado el pivote, se permuta y se divide cada elemento de la fila i por Aij.
+        // Así, Aij = 1. Luego, se resta a cada fila k != i la fila i multiplicada por Akj. Así, los elementos
+        // de la columna j quedan en 0 para esas filas.
+        //
+        // Todo esto para que quede la matriz en forma escalonada reducida de Gauss-Jordan.
+
+        let mut i: usize = 0;
+        let mut j: usize = 0;
+        while i < matrix.rows && j < matrix.cols {
+            // Obtengo el elemento de la diagonal (Aij, que será el pivote)
+            let mut pivot = matrix.get(i, j).unwrap();
+            if nearly_equal(pivot, 0.0) {
+                // Busco la primera fila tal que Akj != 0
+                let mut found = false;
+                // Solo busco en las filas i+1 a rows-1, ya que las filas anteriores ya están en 0
+                let mut k = i + 1;
+                while !found && k < matrix.rows {
+                    pivot = matrix.get(k, j).unwrap();
+                    if nearly_equal(pivot, 0.0) {
+                        k += 1;
+                    } else {
+                        found = true;
+                    }
+                }
+                if !found {
+                    // No encontré ningún elemento no nulo en la columna j
+                    // Por lo tanto, paso a la siguiente columna
+                    j += 1;
+                    continue;
+                } else {
+                    // Permuto la fila k con la fila i
+                    matrix.swap_rows(k, i)?;
+                }
+            }
+
+            // Divido la fila i por Aij, así Aij = 1
+            let factor = 1.0 / pivot;
+            matrix.scale_row(i, factor)?;
+
+            // Ahora, toca restar a cada fila k != i la fila i multiplicada por Akj.
+            for k in 0..matrix.rows {
+                if k != i {
+                    // factor = -Akj
+                    let factor = -matrix.get(k, j)?;
+
+                    // Sumo a cada elemento de la fila k la fila i multiplicada por el factor,
+                    // así los elementos de la columna k quedan en 0.
+                    matrix.add_row(k, i, factor)?;
+                }
+            }
+
+            // Avanzo en diagonal
+            i += 1;
+            j += 1;
+        }
+
+        Ok(matrix)
+    }
+
+    /// Calcula la factorización LU de la matriz, con pivoteo parcial.
+    /// Retorna las matrices (P, L, U) tales que PA = LU, donde P es una matriz de
+    /// permutación, L es triangular inferior con unos en la diagonal y U es
+    /// triangular superior.
+    pub fn lu(&self) -> Result<(Matrix, Matrix, Matrix), &'static str> {
+        if !self.is_square() {
+            return Err("La factorización LU solo está implementada para matrices cuadradas");
+        }
+
+        let n = self.rows;
+        let mut p = Matrix::identity(n);
+        let mut l = Matrix::new(n, n);
+        let mut u = self.clone();
+
+        // Es la misma eliminación gaussiana que se usa para el determinante, pero se
+        // guardan los factores usados en cada operación tipo III en L, y las
+        // permutaciones en P.
+        for k in 0..n {
+            // Busco la fila con el mayor |Uik| (que será el pivote)
+            let i = u.pivot_row(k, k)?;
+            if i != k {
+                // Permuto las filas en U y en P. En L también, ya que hasta ahora solo
+                // tiene completas las columnas anteriores a k.
+                u.swap_rows(k, i)?;
+                p.swap_rows(k, i)?;
+                l.swap_rows(k, i)?;
+            }
+
+            let pivot = u.get(k, k)?;
+            if nearly_equal(pivot, 0.0) {
+                // La columna ya tiene ceros debajo de la diagonal, no hay nada que eliminar.
+                continue;
+            }
+
+            for i in (k + 1)..n {
+                // factor = Uik / Ukk
+                let factor = u.get(i, k)? / pivot;
+                l.set(i, k, factor)?;
+                u.add_row(i, k, -factor)?;
+                // Se fija en 0 para evitar errores de redondeo.
+                u.set(i, k, 0.0)?;
+            }
+        }
+
+        // Finalmente, se agregan los unos de la diagonal de L.
+        for k in 0..n {
+            l.set(k, k, 1.0)?;
+        }
+
+        Ok((p, l, u))
+    }
+
+    /// Resuelve el sistema Ux = b por sustitución hacia atrás, donde U es esta matriz
+    /// (triangular superior) y b es un vector columna.
+    /// Todavía no se usa fuera de las pruebas: es la base para un `linsolve` más rápido.
+    #[allow(dead_code)]
+    pub fn solve_upper_triangular(&self, b: &Matrix) -> Result<Matrix, &'static str> {
+        if !self.is_upper_triangular() {
+            return Err("La matriz debe ser cuadrada y triangular superior");
+        }
+        self.check_triangular_system(b)?;
+
+        // Se despeja desde la última incógnita hacia la primera:
+        // xi = (bi - sum(Uij * xj, j > i)) / Uii
+        let n = self.rows;
+        let mut x = Matrix::new(n, 1);
+        for i in (0..n).rev() {
+            let mut sum = b.get(i, 0)?;
+            for j in (i + 1)..n {
+                sum -= self.get(i, j)? * x.get(j, 0)?;
+            }
+            x.set(i, 0, sum / self.get(i, i)?)?;
+        }
+        Ok(x)
+    }
+
+    /// Resuelve el sistema Lx = b por sustitución hacia adelante, donde L es esta
+    /// matriz (triangular inferior) y b es un vector columna.
+    /// Al igual que `solve_upper_triangular`, todavía no se usa fuera de las pruebas.
+    #[allow(dead_code)]
+    pub fn solve_lower_triangular(&self, b: &Matrix) -> Result<Matrix, &'static str> {
+        if !self.is_lower_triangular() {
+            return Err("La matriz debe ser cuadrada y triangular inferior");
+        }
+        self.check_triangular_system(b)?;
+
+        // Se despeja desde la primera incógnita hacia la última:
+        // xi = (bi - sum(Lij * xj, j < i)) / Lii
+        let n = self.rows;
+        let mut x = Matrix::new(n, 1);
+        for i in 0..n {
+            let mut sum = b.get(i, 0)?;
+            for j in 0..i {
+                sum -= self.get(i, j)? * x.get(j, 0)?;
+            }
+            x.set(i, 0, sum / self.get(i, i)?)?;
+        }
+        Ok(x)
+    }
+
+    /// Comprueba que b sea un vector columna con tantas filas como la matriz y que la
+    /// diagonal no tenga ceros, para poder resolver el sistema por sustitución.
+    fn check_triangular_system(&self, b: &Matrix) -> Result<(), &'static str> {
+        if b.cols != 1 || b.rows != self.rows {
+            return Err("b debe ser un vector columna con tantas filas como la matriz");
+        }
+        if (0..self.rows).any(|i| nearly_equal(self.get(i, i).unwrap(), 0.0)) {
+            return Err("La matriz tiene un 0 en la diagonal, por lo que el sistema no tiene solución única");
+        }
+        Ok(())
+    }
+
+    /// Calcula los autovalores de una matriz simétrica y los retorna en un vector
+    /// columna, ordenados de menor a mayor.
+    pub fn eigenvalues_symmetric(&self) -> Result<Matrix, &'static str> {
+        let (mut eigenvalues, _) = self.diagonalize_symmetric()?;
+        eigenvalues.sort_by(|a, b| a.total_cmp(b));
+
+        let mut result = Matrix::new(eigenvalues.len(), 1);
+        for (i, val) in eigenvalues.into_iter().enumerate() {
+            result.set(i, 0, val)?;
+        }
+        Ok(result)
+    }
+
+    /// Diagonaliza una matriz simétrica como A = VDVᵀ, donde D es diagonal y V es
+    /// ortogonal. Retorna los autovalores (la diagonal de D, sin ordenar) y la matriz V,
+    /// cuyas columnas son los autovectores correspondientes.
+    /// Se calculan con el método de rotaciones de Jacobi (cíclico).
+    fn diagonalize_symmetric(&self) -> Result<(Vec<MatrixItem>, Matrix), &'static str> {
+        if !self.is_square() {
+            return Err("Los autovalores solo están definidos para matrices cuadradas");
+        }
+
+        // Cantidad máxima de barridos. En la práctica converge en muy pocos.
+        const MAX_SWEEPS: usize = 100;
+
+        let n = self.rows;
+        let mut matrix = self.clone();
+        let mut vectors = Matrix::identity(n);
+
+        // La estrategia es aplicar rotaciones A' = JᵀAJ, donde cada rotación J anula un
+        // elemento Apq fuera de la diagonal. Como las rotaciones son ortogonales, A' tiene
+        // los mismos autovalores que A.
+        //
+        // Anular un elemento puede volver a "ensuciar" otros, pero la suma de los cuadrados
+        // de los elementos fuera de la diagonal decrece en cada rotación. Entonces, se
+        // barren todos los pares (p, q) una y otra vez hasta que la matriz es diagonal.
+        // Los autovalores son los elementos de la diagonal, y acumulando las rotaciones
+        // V = J₁J₂...Jₖ se obtienen los autovectores.
+        for _ in 0..MAX_SWEEPS {
+            let mut rotated = false;
+
+            for p in 0..n {
+                for q in (p + 1)..n {
+                    let apq = matrix.get(p, q)?;
+                    let app = matrix.get(p, p)?;
+                    let aqq = matrix.get(q, q)?;
+
+                    // Si Apq es despreciable frente a la diagonal, se lo considera 0.
+                    if nearly_equal(app + apq, app) && nearly_equal(aqq + apq, aqq) {
+                        matrix.set(p, q, 0.0)?;
+                        matrix.set(q, p, 0.0)?;
+                        continue;
+                    }
+                    rotated = true;
+
+                    // Se calcula el ángulo de la rotación (su coseno c y su seno s) tal que
+                    // el nuevo Apq sea 0. Se usa la raíz más chica de t² + 2θt - 1 = 0 (t = tan)
+                    // por estabilidad numérica.
+                    let theta = (aqq - app) / (2.0 * apq);
+                    let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+
+                    // A' = AJ: se rotan las columnas p y q
+                    for k in 0..n {
+                        let akp = matrix.get(k, p)?;
+                        let akq = matrix.get(k, q)?;
+                        matrix.set(k, p, c * akp - s * akq)?;
+                        matrix.set(k, q, s * akp + c * akq)?;
+                    }
+                    // A' = JᵀA: se rotan las filas p y q
+                    for k in 0..n {
+                        let apk = matrix.get(p, k)?;
+                        let aqk = matrix.get(q, k)?;
+                        matrix.set(p, k, c * apk - s * aqk)?;
+                        matrix.set(q, k, s * apk + c * aqk)?;
+                    }
+                    // V' = VJ: se rotan las columnas p y q
+                    for k in 0..n {
+                        let vkp = vectors.get(k, p)?;
+                        let vkq = vectors.get(k, q)?;
+                        vectors.set(k, p, c * vkp - s * vkq)?;
+                        vectors.set(k, q, s * vkp + c * vkq)?;
+                    }
+                }
+            }
+
+            if !rotated {
+                // La matriz ya es diagonal.
+                let eigenvalues = (0..n).map(|i| matrix.get(i, i).unwrap()).collect();
+                return Ok((eigenvalues, vectors));
+            }
+        }
+
+        Err("El cálculo de los autovalores no convergió")
+    }
+
+    /// Eleva una matriz simétrica a un exponente real cualquiera.
+    /// Como A = VDVᵀ y V es ortogonal, Aⁿ = VDⁿVᵀ, donde Dⁿ se calcula elevando cada
+    /// autovalor. Para que esté definida, los autovalores no pueden ser negativos (y
+    /// tampoco 0 si el exponente es negativo).
+    pub fn pow_spd(&self, exp: f64) -> Result<Matrix, String> {
+        let (eigenvalues, vectors) = self.diagonalize_symmetric()?;
+
+        let mut diagonal = Matrix::new(self.rows, self.cols);
+        for (i, &val) in eigenvalues.iter().enumerate() {
+            // Un autovalor negativo despreciable es un error de redondeo.
+            let val = if nearly_equal(val, 0.0) { 0.0 } else { val };
+            if val < 0.0 || (val == 0.0 && exp < 0.0) {
+                return Err(
+                    "Las potencias con exponente no entero solo están implementadas para matrices simétricas definidas positivas"
+                        .to_string(),
+                );
+            }
+            diagonal.set(i, i, val.powf(exp))?;
+        }
+
+        let result = Matrix::multiply(&vectors, &diagonal)?;
+        Ok(Matrix::multiply(&result, &vectors.transpose())?)
+    }
+
+    /// Retorna la pseudoinversa de Moore-Penrose de la matriz, siempre que tenga rango
+    /// completo:
+    /// - Si tiene rango columna completo (M >= N), es (AᵀA)⁻¹Aᵀ, y cumple A⁺A = I.
+    /// - Si tiene rango fila completo (M < N), es Aᵀ(AAᵀ)⁻¹, y cumple AA⁺ = I.
+    pub fn pseudo_inverse(&self) -> Result<Matrix, String> {
+        let transpose = self.transpose();
+        let rank_deficient = |_| {
+            "La pseudoinversa solo está implementada para matrices de rango completo".to_string()
+        };
+
+        if self.rows >= self.cols {
+            let ata = Matrix::multiply(&transpose, self)?;
+            let inverse = ata.inverse().map_err(rank_deficient)?;
+            Ok(Matrix::multiply(&inverse, &transpose)?)
+        } else {
+            let aat = Matrix::multiply(self, &transpose)?;
+            let inverse = aat.inverse().map_err(rank_deficient)?;
+            Ok(Matrix::multiply(&transpose, &inverse)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Rng;
+
+    /// Crea la matriz de Hilbert de nxn, con elementos 1/(i + j + 1). Es invertible,
+    /// pero está muy mal condicionada.
+    fn hilbert(n: usize) -> Matrix {
+        let mut matrix = Matrix::new(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                matrix.set(i, j, 1.0 / (i + j + 1) as f64).unwrap();
+            }
+        }
+        matrix
+    }
+
+    /// Crea una matriz de rows x cols con elementos aleatorios en [0, 1).
+    fn random(rows: usize, cols: usize, seed: u64) -> Matrix {
+        let mut rng = Rng::new(seed);
+        let data: Vec<MatrixItem> = (0..rows * cols).map(|_| rng.next_f64()).collect();
+        Matrix::from_row_slice(rows, cols, &data).unwrap()
+    }
+
+    /// Multiplica dos matrices con la definición, elemento a elemento, para comparar
+    /// con `Matrix::multiply`.
+    fn naive_multiply(left: &Matrix, right: &Matrix) -> Matrix {
+        let mut result = Matrix::new(left.rows(), right.cols());
+        for i in 0..left.rows() {
+            for j in 0..right.cols() {
+                let mut sum = 0.0;
+                for k in 0..left.cols() {
+                    sum += left.get(i, k).unwrap() * right.get(k, j).unwrap();
+                }
+                result.set(i, j, sum).unwrap();
+            }
+        }
+        result
+    }
+
+    /// Comprueba que dos matrices tengan la misma dimensión y que sus elementos no
+    /// difieran en más de `tolerance`.
+    fn assert_close(a: &Matrix, b: &Matrix, tolerance: f64) {
+        assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+        for (i, j, val) in a {
+            let expected = b.get(i, j).unwrap();
+            assert!(
+                (val - expected).abs() <= tolerance,
+                "({}, {}): {} != {}",
+                i,
+                j,
+                val,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn determinant_of_singular_matrix_is_zero() {
+        let a = Matrix::from_row_slice(3, 3, &[1., 2., 3., 4., 5., 6., 7., 8., 9.]).unwrap();
+        assert_eq!(a.determinant(), Ok(0.0));
+        assert!(a.inverse().is_err());
+
+        let b = Matrix::from_row_slice(2, 2, &[0.1, 0.2, 0.3, 0.6]).unwrap();
+        assert_eq!(b.determinant(), Ok(0.0));
+    }
+
+    #[test]
+    fn ill_conditioned_matrix_is_invertible() {
+        let h = hilbert(6);
+        let determinant = h.determinant().unwrap();
+        assert!((determinant - 5.3673e-18).abs() < 1e-21);
+
+        let product = Matrix::multiply(&h.inverse().unwrap(), &h).unwrap();
+        assert_close(&product, &Matrix::identity(6), 1e-6);
+
+        let h2 = Matrix::from_row_slice(2, 2, &[1.0, 0.5, 0.5, 1.0 / 3.0]).unwrap();
+        let inverse = Matrix::from_row_slice(2, 2, &[4.0, -6.0, -6.0, 12.0]).unwrap();
+        assert_close(&h2.inverse().unwrap(), &inverse, 1e-9);
+    }
+
+    #[test]
+    fn large_entry_does_not_hide_the_other_pivots() {
+        let a = Matrix::from_row_slice(3, 3, &[1e13, 1., 0., 1., 1., 0., 0., 0., 1.]).unwrap();
+        assert_eq!(a.determinant(), Ok(9999999999999.0));
+
+        let product = Matrix::multiply(&a, &a.inverse().unwrap()).unwrap();
+        assert_close(&product, &Matrix::identity(3), 1e-9);
+    }
+
+    #[test]
+    fn expm_rejects_non_finite_entries() {
+        for val in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            let a = Matrix::from_row_slice(2, 2, &[val, 0.0, 0.0, 1.0]).unwrap();
+            assert!(a.expm().is_err());
+        }
+
+        let a = Matrix::from_row_slice(2, 2, &[1.0, 0.0, 0.0, 2.0]).unwrap();
+        let expected = Matrix::from_row_slice(2, 2, &[1f64.exp(), 0.0, 0.0, 2f64.exp()]).unwrap();
+        assert_close(&a.expm().unwrap(), &expected, 1e-12);
+    }
+
+    #[test]
+    fn row_operations() {
+        let mut a = Matrix::from_row_slice(3, 2, &[1., 2., 3., 4., 5., 6.]).unwrap();
+
+        a.swap_rows(0, 2).unwrap();
+        let expected = Matrix::from_row_slice(3, 2, &[5., 6., 3., 4., 1., 2.]).unwrap();
+        assert_close(&a, &expected, 0.0);
+
+        a.scale_row(1, -2.0).unwrap();
+        let expected = Matrix::from_row_slice(3, 2, &[5., 6., -6., -8., 1., 2.]).unwrap();
+        assert_close(&a, &expected, 0.0);
+
+        a.add_row(0, 2, 10.0).unwrap();
+        let expected = Matrix::from_row_slice(3, 2, &[15., 26., -6., -8., 1., 2.]).unwrap();
+        assert_close(&a, &expected, 0.0);
+    }
+
+    #[test]
+    fn row_operations_check_bounds() {
+        let mut a = Matrix::from_row_slice(2, 2, &[1., 2., 3., 4.]).unwrap();
+        let original = a.clone();
+
+        assert_eq!(a.swap_rows(0, 2), Err("Índice fuera de rango"));
+        assert_eq!(a.swap_rows(2, 0), Err("Índice fuera de rango"));
+        assert_eq!(a.scale_row(2, 3.0), Err("Índice fuera de rango"));
+        assert_eq!(a.add_row(2, 0, 1.0), Err("Índice fuera de rango"));
+        assert_eq!(a.add_row(0, 2, 1.0), Err("Índice fuera de rango"));
+        assert_close(&a, &original, 0.0);
+    }
+
+    #[test]
+    fn inverse_of_random_matrix() {
+        let a = random(40, 40, 546);
+        let product = Matrix::multiply(&a, &a.inverse().unwrap()).unwrap();
+        assert_close(&product, &Matrix::identity(40), 1e-8);
+    }
+
+    #[test]
+    fn pivoting_handles_zero_and_tiny_pivots() {
+        let a = Matrix::from_row_slice(3, 3, &[0., 1., 2., 1., 0., 3., 4., -3., 8.]).unwrap();
+        assert!((a.determinant().unwrap() - -2.0).abs() < 1e-12);
+        let product = Matrix::multiply(&a, &a.inverse().unwrap()).unwrap();
+        assert_close(&product, &Matrix::identity(3), 1e-12);
+
+        // det(hilb(4)) = 1/6048000
+        let determinant = hilbert(4).determinant().unwrap();
+        let expected = 1.0 / 6048000.0;
+        assert!(((determinant - expected) / expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lu_reconstructs_the_permuted_matrix() {
+        let a = Matrix::from_row_slice(3, 3, &[0., 2., 1., 4., 1., -2., 2., 3., 5.]).unwrap();
+        let (p, l, u) = a.lu().unwrap();
+        assert!(l.is_lower_triangular() && u.is_upper_triangular());
+        for i in 0..3 {
+            assert_eq!(l.get(i, i), Ok(1.0));
+        }
+        let lu = Matrix::multiply(&l, &u).unwrap();
+        let pa = Matrix::multiply(&p, &a).unwrap();
+        assert_close(&lu, &pa, 1e-12);
+
+        let b = random(6, 6, 552);
+        let (p, l, u) = b.lu().unwrap();
+        let lu = Matrix::multiply(&l, &u).unwrap();
+        assert_close(&lu, &Matrix::multiply(&p, &b).unwrap(), 1e-12);
+    }
+
+    #[test]
+    fn adjugate_times_matrix_is_determinant_times_identity() {
+        let a = Matrix::from_row_slice(3, 3, &[2., -1., 0., 1., 3., 4., 0., 5., -2.]).unwrap();
+        let product = Matrix::multiply(&a, &a.adjugate().unwrap()).unwrap();
+        let determinant = a.determinant().unwrap();
+        assert!((determinant - -54.0).abs() < 1e-12);
+        assert_close(&product, &Matrix::identity(3).scale(determinant), 1e-9);
+
+        assert!(Matrix::new(2, 3).adjugate().is_err());
+    }
+
+    #[test]
+    fn exponential_of_zero_and_diagonal_matrices() {
+        assert_close(
+            &Matrix::new(3, 3).expm().unwrap(),
+            &Matrix::identity(3),
+            0.0,
+        );
+
+        let (a, b) = (1.5, -0.5);
+        let d = Matrix::from_row_slice(2, 2, &[a, 0.0, 0.0, b]).unwrap();
+        let expected = Matrix::from_row_slice(2, 2, &[a.exp(), 0.0, 0.0, b.exp()]).unwrap();
+        assert_close(&d.expm().unwrap(), &expected, 1e-12);
+
+        assert!(Matrix::new(2, 3).expm().is_err());
+    }
+
+    #[test]
+    fn from_2d_ignores_empty_rows_and_rejects_ragged_ones() {
+        let empty = Matrix::from_2d(vec![]).unwrap();
+        assert_eq!((empty.rows(), empty.cols()), (0, 0));
+        let empty_row = Matrix::from_2d(vec![vec![]]).unwrap();
+        assert_eq!((empty_row.rows(), empty_row.cols()), (0, 0));
+
+        let trailing = Matrix::from_2d(vec![vec![1.0, 2.0], vec![]]).unwrap();
+        assert_eq!((trailing.rows(), trailing.cols()), (1, 2));
+
+        assert_eq!(
+            Matrix::from_2d(vec![vec![1.0, 2.0], vec![3.0]]).unwrap_err(),
+            "La matriz está mal declarada: el número de columnas no es consistente"
+        );
+    }
+
+    // Correr con `cargo test --release -- --ignored` para ver cuánto tarda.
+    #[test]
+    #[ignore]
+    fn multiply_large_matrices_benchmark() {
+        let (a, b) = (random(200, 200, 1), random(200, 200, 2));
+        let start = std::time::Instant::now();
+        let product = Matrix::multiply(&a, &b).unwrap();
+        println!("200x200 * 200x200: {:?}", start.elapsed());
+        assert_close(&product, &naive_multiply(&a, &b), 0.0);
+    }
+
+    #[test]
+    fn in_place_operations_match_allocating_ones() {
+        let a = Matrix::from_row_slice(2, 2, &[1., 2., 3., 4.]).unwrap();
+        let b = Matrix::from_row_slice(2, 2, &[0.5, -1., 2., 8.]).unwrap();
+
+        let mut sum = a.clone();
+        sum.add_assign(&b).unwrap();
+        assert_close(&sum, &Matrix::add(&a, &b).unwrap(), 0.0);
+
+        let mut scaled = a.clone();
+        scaled.scale_in_place(-3.0);
+        assert_close(&scaled, &a.scale(-3.0), 0.0);
+
+        // Si las dimensiones no coinciden, la matriz no se modifica.
+        let mut unchanged = a.clone();
+        assert!(unchanged.add_assign(&Matrix::new(2, 3)).is_err());
+        assert_close(&unchanged, &a, 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_multiplication_matches_serial() {
+        let (a, b) = (random(300, 300, 3), random(300, 300, 4));
+        // 300^3 supera PARALLEL_THRESHOLD, así que se multiplica en paralelo.
+        assert_close(
+            &Matrix::multiply(&a, &b).unwrap(),
+            &naive_multiply(&a, &b),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn from_row_slice_fills_row_by_row() {
+        let a = Matrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., 6.]).unwrap();
+        assert_eq!((a.rows(), a.cols()), (2, 3));
+        assert_eq!(a.get(0, 2), Ok(3.0));
+        assert_eq!(a.get(1, 0), Ok(4.0));
+        let b = Matrix::from_2d(vec![vec![1., 2., 3.], vec![4., 5., 6.]]).unwrap();
+        assert_close(&a, &b, 0.0);
+
+        let error = "La cantidad de elementos no coincide con la dimensión de la matriz";
+        assert_eq!(Matrix::from_row_slice(2, 3, &[1., 2.]).unwrap_err(), error);
+        assert_eq!(Matrix::from_row_slice(1, 1, &[1., 2.]).unwrap_err(), error);
+        assert!(Matrix::from_row_slice(0, 5, &[]).is_ok());
+    }
+
+    #[test]
+    fn flips() {
+        let a = Matrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., 6.]).unwrap();
+        let lr = Matrix::from_row_slice(2, 3, &[3., 2., 1., 6., 5., 4.]).unwrap();
+        let ud = Matrix::from_row_slice(2, 3, &[4., 5., 6., 1., 2., 3.]).unwrap();
+        assert_close(&a.fliplr(), &lr, 0.0);
+        assert_close(&a.flipud(), &ud, 0.0);
+        assert_close(&a.fliplr().fliplr(), &a, 0.0);
+        assert_close(&a.flipud().flipud(), &a, 0.0);
+    }
+
+    #[test]
+    fn rotations() {
+        let a = Matrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., 6.]).unwrap();
+        let once = Matrix::from_row_slice(3, 2, &[3., 6., 2., 5., 1., 4.]).unwrap();
+        assert_close(&a.rot90(1), &once, 0.0);
+        let clockwise = Matrix::from_row_slice(3, 2, &[4., 1., 5., 2., 6., 3.]).unwrap();
+        assert_close(&a.rot90(-1), &clockwise, 0.0);
+        assert_close(&a.rot90(1).rot90(1).rot90(1).rot90(1), &a, 0.0);
+        assert_close(&a.rot90(4), &a, 0.0);
+        assert_close(&a.rot90(2), &a.fliplr().flipud(), 0.0);
+    }
+
+    #[test]
+    fn triangular_parts() {
+        let a = Matrix::from_row_slice(3, 3, &[1., 2., 3., 4., 5., 6., 7., 8., 9.]).unwrap();
+        let lower = Matrix::from_row_slice(3, 3, &[1., 0., 0., 4., 5., 0., 7., 8., 9.]).unwrap();
+        let upper = Matrix::from_row_slice(3, 3, &[1., 2., 3., 0., 5., 6., 0., 0., 9.]).unwrap();
+        assert_close(&a.tril(0), &lower, 0.0);
+        assert_close(&a.triu(0), &upper, 0.0);
+
+        let lower_1 = Matrix::from_row_slice(3, 3, &[1., 2., 0., 4., 5., 6., 7., 8., 9.]).unwrap();
+        let upper_1 = Matrix::from_row_slice(3, 3, &[0., 2., 3., 0., 0., 6., 0., 0., 0.]).unwrap();
+        assert_close(&a.tril(1), &lower_1, 0.0);
+        assert_close(&a.triu(1), &upper_1, 0.0);
+    }
+
+    #[test]
+    fn symmetry_is_checked_with_tolerance() {
+        let a = Matrix::from_row_slice(2, 2, &[1., 2., 2., 3.]).unwrap();
+        assert!(a.is_symmetric());
+        let nearly = Matrix::from_row_slice(2, 2, &[1., 2., 2. + 1e-14, 3.]).unwrap();
+        assert!(nearly.is_symmetric());
+        let b = Matrix::from_row_slice(2, 2, &[1., 2., 2.1, 3.]).unwrap();
+        assert!(!b.is_symmetric());
+        assert!(!Matrix::new(2, 3).is_symmetric());
+    }
+
+    #[test]
+    fn diagonal_fast_paths_match_the_general_ones() {
+        let d = Matrix::from_row_slice(3, 3, &[2., 0., 0., 0., -3., 0., 0., 0., 0.5]).unwrap();
+        assert!(d.is_diagonal());
+        assert_eq!(d.determinant(), Ok(-3.0));
+        let cube = Matrix::multiply(&Matrix::multiply(&d, &d).unwrap(), &d).unwrap();
+        assert_close(&d.pow(3.0).unwrap(), &cube, 1e-12);
+        assert_close(&d.pow(-1.0).unwrap(), &d.inverse().unwrap(), 1e-12);
+
+        let a = Matrix::from_row_slice(2, 2, &[2., 1., 0., 3.]).unwrap();
+        assert!(!a.is_diagonal());
+        assert_close(
+            &a.pow(2.0).unwrap(),
+            &Matrix::multiply(&a, &a).unwrap(),
+            1e-12,
+        );
+        assert_eq!(a.determinant(), Ok(6.0));
+    }
+
+    #[test]
+    fn closed_forms_for_small_matrices() {
+        let one = Matrix::from_scalar(4.0);
+        assert_eq!(one.determinant(), Ok(4.0));
+        assert_close(&one.inverse().unwrap(), &Matrix::from_scalar(0.25), 0.0);
+        assert!(Matrix::from_scalar(0.0).inverse().is_err());
+
+        let a = Matrix::from_row_slice(2, 2, &[3.0, 1.0, 5.0, 2.0]).unwrap();
+        assert_eq!(a.determinant(), Ok(1.0));
+        let inverse = Matrix::from_row_slice(2, 2, &[2.0, -1.0, -5.0, 3.0]).unwrap();
+        assert_close(&a.inverse().unwrap(), &inverse, 0.0);
+
+        // Con un bloque de 1 en la diagonal, se usa el método general de 3x3.
+        let b = Matrix::from_row_slice(2, 2, &[0.3, 1.7, -2.2, 0.9]).unwrap();
+        let block =
+            Matrix::from_row_slice(3, 3, &[0.3, 1.7, 0., -2.2, 0.9, 0., 0., 0., 1.]).unwrap();
+        let general = block.determinant().unwrap();
+        assert!((b.determinant().unwrap() - general).abs() < 1e-12);
+        let general_inverse = block.inverse().unwrap();
+        let b_inverse = b.inverse().unwrap();
+        for (i, j, val) in &b_inverse {
+            assert!((val - general_inverse.get(i, j).unwrap()).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn triangular_systems() {
+        let u = Matrix::from_row_slice(3, 3, &[2., 1., -1., 0., 3., 2., 0., 0., 4.]).unwrap();
+        let b = Matrix::from_row_slice(3, 1, &[1., 12., 12.]).unwrap();
+        let x = Matrix::from_row_slice(3, 1, &[1., 2., 3.]).unwrap();
+        assert_close(&u.solve_upper_triangular(&b).unwrap(), &x, 1e-12);
+
+        let l = Matrix::from_row_slice(3, 3, &[1., 0., 0., 2., 3., 0., -1., 4., 5.]).unwrap();
+        let b = Matrix::from_row_slice(3, 1, &[1., -1., 5.]).unwrap();
+        let x = Matrix::from_row_slice(3, 1, &[1., -1., 2.]).unwrap();
+        assert_close(&l.solve_lower_triangular(&b).unwrap(), &x, 1e-12);
+
+        // Cada una solo acepta su tipo de matriz triangular.
+        assert!(u.solve_lower_triangular(&b).is_err());
+        assert!(l.solve_upper_triangular(&b).is_err());
+        let zero_pivot = Matrix::from_row_slice(2, 2, &[1., 1., 0., 0.]).unwrap();
+        let b = Matrix::from_row_slice(2, 1, &[1., 1.]).unwrap();
+        assert_eq!(
+            zero_pivot.solve_upper_triangular(&b).unwrap_err(),
+            "La matriz tiene un 0 en la diagonal, por lo que el sistema no tiene solución única"
+        );
+        assert!(u.solve_upper_triangular(&b).is_err());
+    }
 }
@@ -1,9 +1,10 @@
 // Aquí está el código que pasa cada línea de entrada en
 // texto plano a una estructura de datos más manejable.
 
+use pest::error::{Error, ErrorVariant, InputLocation};
 use pest::iterators::Pairs;
 use pest::pratt_parser::PrattParser;
-use pest::Parser;
+use pest::{Parser, Position};
 
 #[derive(pest_derive::Parser)]
 #[grammar = "parser/grammar.pest"]
@@ -13,8 +14,12 @@ pub struct ProgramParser;
 pub enum UnaryOp {
     Positive,
     Negate,
+    Not,
     Factorial,
+    /// Traspuesta conjugada (`'`).
     Transpose,
+    /// Traspuesta sin conjugar (`.'`).
+    TransposeNoConj,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -25,17 +30,33 @@ pub enum BinaryOp {
     Divide,
     RightDivide,
     Power,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
 }
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum AstNode {
     Ident(String),
     Scalar(f64),
+    /// Texto literal, ya con las secuencias de escape reemplazadas.
+    Str(String),
     Matrix(Vec<Vec<AstNode>>),
     Call {
         func: String,
         args: Vec<AstNode>,
     },
+    /// Función anónima. `source` es el texto con el que se escribió, para mostrarla.
+    Lambda {
+        params: Vec<String>,
+        body: Box<AstNode>,
+        source: String,
+    },
     UnaryOp {
         op: UnaryOp,
         expr: Box<AstNode>,
@@ -47,10 +68,40 @@ pub enum AstNode {
     },
 }
 
+impl AstNode {
+    /// Agrega a `names` los nombres de todas las variables y funciones que aparecen
+    /// en la expresión.
+    pub fn collect_names(&self, names: &mut Vec<String>) {
+        match self {
+            AstNode::Ident(name) => names.push(name.clone()),
+            AstNode::Scalar(_) | AstNode::Str(_) => {}
+            AstNode::Matrix(rows) => {
+                for node in rows.iter().flatten() {
+                    node.collect_names(names);
+                }
+            }
+            AstNode::Call { func, args } => {
+                names.push(func.clone());
+                for arg in args {
+                    arg.collect_names(names);
+                }
+            }
+            AstNode::Lambda { body, .. } => body.collect_names(names),
+            AstNode::UnaryOp { expr, .. } => expr.collect_names(names),
+            AstNode::BinaryOp { left, right, .. } => {
+                left.collect_names(names);
+                right.collect_names(names);
+            }
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct Statement {
     pub assign_to: Option<String>,
     pub expr: AstNode,
+    /// Si la sentencia termina en ";", no se muestra su resultado.
+    pub silent: bool,
 }
 
 lazy_static::lazy_static! {
@@ -60,26 +111,74 @@ lazy_static::lazy_static! {
 
       // Precedence is defined lowest to highest
       PrattParser::new()
+        .op(Op::infix(logical_or, Left))
+        .op(Op::infix(logical_and, Left))
+        .op(Op::infix(less, Left)
+            | Op::infix(greater, Left)
+            | Op::infix(less_equal, Left)
+            | Op::infix(greater_equal, Left)
+            | Op::infix(equal, Left)
+            | Op::infix(not_equal, Left))
         .op(Op::infix(add, Left) | Op::infix(subtract, Left))
         .op(Op::infix(multiply, Left)
+            | Op::infix(implicit_multiply, Left)
             | Op::infix(divide, Left)
             | Op::infix(right_divide, Left))
         .op(Op::infix(power, Right))
-        .op(Op::postfix(factorial) | Op::postfix(transpose))
-        .op(Op::prefix(positive) | Op::prefix(negative))
+        .op(Op::postfix(factorial) | Op::postfix(transpose) | Op::postfix(transpose_no_conj))
+        .op(Op::prefix(positive) | Op::prefix(negative) | Op::prefix(logical_not))
   };
 }
 
+/// Convierte los dígitos de un entero en base `radix` a un número real. Se acumula
+/// directamente en f64 para que un literal muy largo no desborde (a partir de 2^53
+/// se pierde precisión, como con cualquier número grande).
+fn parse_radix(digits: &str, radix: u32) -> f64 {
+    digits.chars().fold(0.0, |acc, digit| {
+        acc * radix as f64 + digit.to_digit(radix).unwrap() as f64
+    })
+}
+
+/// Reemplaza las secuencias de escape de un texto literal (\", \n y \\) por los
+/// caracteres que representan. Cualquier otra secuencia se deja como está.
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
 fn parse_expr(pairs: Pairs<Rule>) -> AstNode {
     PRATT_PARSER
         .map_primary(|primary| match primary.as_rule() {
             Rule::expr => parse_expr(primary.into_inner()),
-            Rule::number => AstNode::Scalar(primary.as_str().parse::<f64>().unwrap()),
+            Rule::number | Rule::coefficient => {
+                // Se quitan los guiones bajos que separan los dígitos (como en 1_000).
+                AstNode::Scalar(primary.as_str().replace('_', "").parse::<f64>().unwrap())
+            }
+            Rule::hex => AstNode::Scalar(parse_radix(&primary.as_str()[2..], 16)),
+            Rule::bin => AstNode::Scalar(parse_radix(&primary.as_str()[2..], 2)),
             Rule::ident => AstNode::Ident(primary.as_str().to_string()),
+            Rule::string => AstNode::Str(unescape(primary.into_inner().as_str())),
             Rule::matrix => {
-                let mut pair = primary.into_inner();
+                let pair = primary.into_inner();
                 let mut elements: Vec<Vec<AstNode>> = vec![vec![]];
-                while let Some(child) = pair.next() {
+                for child in pair {
                     match child.as_rule() {
                         Rule::matrix_sep => {
                             if child.as_str() == ";" {
@@ -103,7 +202,7 @@ fn parse_expr(pairs: Pairs<Rule>) -> AstNode {
                 let mut pair = primary.into_inner();
                 let func = pair.next().unwrap();
                 let mut args = Vec::<AstNode>::new();
-                while let Some(arg) = pair.next() {
+                for arg in pair {
                     args.push(parse_expr(arg.into_inner()));
                 }
                 AstNode::Call {
@@ -111,16 +210,43 @@ fn parse_expr(pairs: Pairs<Rule>) -> AstNode {
                     args,
                 }
             }
+            Rule::lambda => {
+                let source = primary.as_str().to_string();
+                let mut params = Vec::<String>::new();
+                let mut body = None;
+                for child in primary.into_inner() {
+                    match child.as_rule() {
+                        Rule::ident => params.push(child.as_str().to_string()),
+                        Rule::expr => body = Some(parse_expr(child.into_inner())),
+                        rule => {
+                            unreachable!("Unexpected atom when parsing a lambda, found {:?}", rule)
+                        }
+                    }
+                }
+                AstNode::Lambda {
+                    params,
+                    body: Box::new(body.unwrap()),
+                    source,
+                }
+            }
             rule => unreachable!("Expr::parse expected atom, found {:?}", rule),
         })
         .map_infix(|left, op, right| {
             let op = match op.as_rule() {
                 Rule::add => BinaryOp::Add,
                 Rule::subtract => BinaryOp::Subtract,
-                Rule::multiply => BinaryOp::Multiply,
+                Rule::multiply | Rule::implicit_multiply => BinaryOp::Multiply,
                 Rule::divide => BinaryOp::Divide,
                 Rule::right_divide => BinaryOp::RightDivide,
                 Rule::power => BinaryOp::Power,
+                Rule::less => BinaryOp::Less,
+                Rule::greater => BinaryOp::Greater,
+                Rule::less_equal => BinaryOp::LessEqual,
+                Rule::greater_equal => BinaryOp::GreaterEqual,
+                Rule::equal => BinaryOp::Equal,
+                Rule::not_equal => BinaryOp::NotEqual,
+                Rule::logical_and => BinaryOp::And,
+                Rule::logical_or => BinaryOp::Or,
                 rule => unreachable!("Expr::parse expected infix operation, found {:?}", rule),
             };
             AstNode::BinaryOp {
@@ -133,6 +259,7 @@ fn parse_expr(pairs: Pairs<Rule>) -> AstNode {
             let op = match op.as_rule() {
                 Rule::positive => UnaryOp::Positive,
                 Rule::negative => UnaryOp::Negate,
+                Rule::logical_not => UnaryOp::Not,
                 _ => unreachable!(),
             };
             AstNode::UnaryOp {
@@ -144,6 +271,7 @@ fn parse_expr(pairs: Pairs<Rule>) -> AstNode {
             let op = match op.as_rule() {
                 Rule::factorial => UnaryOp::Factorial,
                 Rule::transpose => UnaryOp::Transpose,
+                Rule::transpose_no_conj => UnaryOp::TransposeNoConj,
                 _ => unreachable!(),
             };
             AstNode::UnaryOp {
@@ -154,10 +282,68 @@ fn parse_expr(pairs: Pairs<Rule>) -> AstNode {
         .parse(pairs)
 }
 
-pub fn parse(source: &str) -> Result<Vec<Statement>, pest::error::Error<Rule>> {
+/// Rechaza las comparaciones encadenadas como `1 < 2 < 3`, que se evaluarían como
+/// `(1 < 2) < 3` (comparando un booleano con 3), lo que casi nunca es lo que se quiere.
+/// Dos comparaciones en el mismo nivel solo pueden estar separadas por `&&` o `||`.
+fn check_chained_comparisons(pairs: Pairs<Rule>) -> Result<(), Error<Rule>> {
+    for pair in pairs {
+        if pair.as_rule() == Rule::expr {
+            let mut comparison = false;
+            for child in pair.clone().into_inner() {
+                match child.as_rule() {
+                    Rule::less
+                    | Rule::greater
+                    | Rule::less_equal
+                    | Rule::greater_equal
+                    | Rule::equal
+                    | Rule::not_equal => {
+                        if comparison {
+                            return Err(Error::new_from_span(
+                                ErrorVariant::CustomError {
+                                    message: "las comparaciones no se pueden encadenar. Use paréntesis o && (por ejemplo, 1 < 2 && 2 < 3)".to_string(),
+                                },
+                                child.as_span(),
+                            ));
+                        }
+                        comparison = true;
+                    }
+                    Rule::logical_and | Rule::logical_or => comparison = false,
+                    _ => {}
+                }
+            }
+        }
+        check_chained_comparisons(pair.into_inner())?;
+    }
+    Ok(())
+}
+
+/// Los elementos de una matriz se separan con "," y no con espacios, como sí se
+/// puede en MATLAB (`[1 2 3]`). Si el error de sintáxis está justo después de un
+/// espacio dentro de un corchete sin cerrar, se explica cómo escribirlo.
+fn explain_space_separated(source: &str, error: Error<Rule>) -> Error<Rule> {
+    let pos = match error.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span(_) => return error,
+    };
+    let before = &source[..pos];
+    let inside_matrix = before.matches('[').count() > before.matches(']').count();
+    if !inside_matrix || !before.ends_with(' ') {
+        return error;
+    }
+    Error::new_from_pos(
+        ErrorVariant::CustomError {
+            message: "los elementos de una matriz se separan con \",\" (por ejemplo, [1, 2, 3]); no se admiten espacios como separador".to_string(),
+        },
+        Position::new(source, pos).unwrap(),
+    )
+}
+
+pub fn parse(source: &str) -> Result<Vec<Statement>, Error<Rule>> {
     let mut statements: Vec<Statement> = vec![];
 
-    let pairs = ProgramParser::parse(Rule::program, source)?;
+    let pairs = ProgramParser::parse(Rule::program, source)
+        .map_err(|error| explain_space_separated(source, error))?;
+    check_chained_comparisons(pairs.clone())?;
     for pair in pairs {
         let statement = match pair.as_rule() {
             Rule::assign => {
@@ -167,6 +353,7 @@ pub fn parse(source: &str) -> Result<Vec<Statement>, pest::error::Error<Rule>> {
                 Statement {
                     assign_to: Some(ident),
                     expr,
+                    silent: false,
                 }
             }
             Rule::expr => {
@@ -174,8 +361,17 @@ pub fn parse(source: &str) -> Result<Vec<Statement>, pest::error::Error<Rule>> {
                 Statement {
                     assign_to: None,
                     expr,
+                    silent: false,
                 }
             }
+            Rule::stmt_sep => {
+                if pair.as_str() == ";" {
+                    if let Some(last) = statements.last_mut() {
+                        last.silent = true;
+                    }
+                }
+                continue;
+            }
             Rule::EOI => break,
             _ => unreachable!(),
         };
@@ -184,3 +380,152 @@ pub fn parse(source: &str) -> Result<Vec<Statement>, pest::error::Error<Rule>> {
 
     Ok(statements)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Obtiene el mensaje de un error de sintáxis con mensaje propio.
+    fn custom_message(source: &str) -> String {
+        match parse(source).unwrap_err().variant {
+            ErrorVariant::CustomError { message } => message,
+            ErrorVariant::ParsingError { .. } => panic!("se esperaba un mensaje propio"),
+        }
+    }
+
+    #[test]
+    fn rejects_space_separated_matrix_elements() {
+        assert!(custom_message("[1 2 3] * [1; 2; 3]").contains("[1, 2, 3]"));
+        assert!(parse("[1, 2, 3] * [1; 2; 3]").is_ok());
+        assert!(parse("[1, 2 + 3]").is_ok());
+    }
+
+    /// Parsea una entrada con una sola sentencia y devuelve su expresión.
+    fn expr(source: &str) -> AstNode {
+        let mut statements = parse(source).unwrap();
+        assert_eq!(statements.len(), 1);
+        statements.remove(0).expr
+    }
+
+    #[test]
+    fn number_literal_is_a_scalar_node() {
+        assert_eq!(
+            parse("42").unwrap(),
+            vec![Statement {
+                assign_to: None,
+                expr: AstNode::Scalar(42.0),
+                silent: false,
+            }]
+        );
+        assert_eq!(expr("x = 42"), AstNode::Scalar(42.0));
+        assert_eq!(parse("x = 42").unwrap()[0].assign_to.as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn comments_are_ignored() {
+        assert_eq!(parse("% solo un comentario").unwrap(), vec![]);
+        assert_eq!(parse("   %").unwrap(), vec![]);
+        let statements = parse("x = 1 + 2 % suma").unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(statements[0].assign_to.as_deref(), Some("x"));
+        assert_eq!(
+            statements[0].expr,
+            AstNode::BinaryOp {
+                op: BinaryOp::Add,
+                left: Box::new(AstNode::Scalar(1.0)),
+                right: Box::new(AstNode::Scalar(2.0)),
+            }
+        );
+        assert_eq!(expr("\"50% off\""), AstNode::Str("50% off".to_string()));
+    }
+
+    #[test]
+    fn statements_separated_by_commas_and_semicolons() {
+        let statements = parse("a=1; b=2, c=3").unwrap();
+        let summary: Vec<(Option<&str>, bool)> = statements
+            .iter()
+            .map(|statement| (statement.assign_to.as_deref(), statement.silent))
+            .collect();
+        assert_eq!(
+            summary,
+            [(Some("a"), true), (Some("b"), false), (Some("c"), false)]
+        );
+        let silent: Vec<bool> = parse("1, 2;")
+            .unwrap()
+            .iter()
+            .map(|statement| statement.silent)
+            .collect();
+        assert_eq!(silent, [false, true]);
+    }
+
+    #[test]
+    fn rejects_chained_comparisons() {
+        assert!(custom_message("1 < 2 < 3").starts_with("las comparaciones no se pueden encadenar"));
+        assert!(
+            custom_message("a == b ~= c").starts_with("las comparaciones no se pueden encadenar")
+        );
+        assert!(parse("(1 < 2) < 3").is_ok());
+        assert!(parse("1 < 2 && 2 < 3").is_ok());
+    }
+
+    #[test]
+    fn distinguishes_transpose_operators() {
+        let transpose = |op| AstNode::UnaryOp {
+            op,
+            expr: Box::new(AstNode::Ident("A".to_string())),
+        };
+        assert_eq!(expr("A'"), transpose(UnaryOp::Transpose));
+        assert_eq!(expr("A.'"), transpose(UnaryOp::TransposeNoConj));
+        assert_eq!(
+            expr("A'.'"),
+            AstNode::UnaryOp {
+                op: UnaryOp::TransposeNoConj,
+                expr: Box::new(transpose(UnaryOp::Transpose)),
+            }
+        );
+    }
+
+    #[test]
+    fn string_literals() {
+        assert_eq!(expr("\"\""), AstNode::Str(String::new()));
+        assert_eq!(
+            expr("\"hola mundo\""),
+            AstNode::Str("hola mundo".to_string())
+        );
+        assert_eq!(
+            expr(r#""dijo \"hola\"\ny se fue \\ \t""#),
+            AstNode::Str("dijo \"hola\"\ny se fue \\ \\t".to_string())
+        );
+        assert!(parse("\"sin cerrar").is_err());
+    }
+
+    #[test]
+    fn number_literals_with_optional_digits() {
+        assert_eq!(expr(".5"), AstNode::Scalar(0.5));
+        assert_eq!(expr("5."), AstNode::Scalar(5.0));
+        assert_eq!(expr("5.0"), AstNode::Scalar(5.0));
+        assert_eq!(expr("1.5e3"), AstNode::Scalar(1500.0));
+        assert_eq!(expr(".5e-1"), AstNode::Scalar(0.05));
+        assert!(parse(".").is_err());
+    }
+
+    #[test]
+    fn hexadecimal_and_binary_literals() {
+        assert_eq!(expr("0xFF"), AstNode::Scalar(255.0));
+        assert_eq!(expr("0xff"), AstNode::Scalar(255.0));
+        assert_eq!(expr("0b101"), AstNode::Scalar(5.0));
+        assert!(parse("0x").is_err());
+        assert!(parse("0b").is_err());
+        assert!(parse("0b102").is_err());
+        assert!(parse("0xFG").is_err());
+    }
+
+    #[test]
+    fn underscores_in_number_literals() {
+        assert_eq!(expr("1_000"), AstNode::Scalar(1000.0));
+        assert_eq!(expr("1_000.250_5"), AstNode::Scalar(1000.2505));
+        for source in ["_1", "1_", "1_.5", "1._5", "1__0"] {
+            assert!(parse(source).is_err(), "{}", source);
+        }
+    }
+}
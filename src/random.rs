@@ -0,0 +1,44 @@
+// En este archivo se implementa un generador de números pseudoaleatorios, para
+// no depender de una biblioteca externa. Excede a la materia.
+//
+// Se usa el algoritmo SplitMix64, que es muy simple y suficiente para generar
+// matrices aleatorias (no sirve para criptografía).
+// Ver https://prng.di.unimi.it/splitmix64.c
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Crea un generador a partir de una semilla. Con la misma semilla, se
+    /// generan siempre los mismos números.
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Crea un generador con una semilla basada en la hora actual.
+    pub fn from_time() -> Rng {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng::new(nanos)
+    }
+
+    /// Genera el siguiente número entero de 64 bits.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Genera un número real uniforme en [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        // Se usan los 53 bits más significativos, que es la precisión de un f64.
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
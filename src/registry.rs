@@ -0,0 +1,466 @@
+// Aquí se registran las funciones disponibles. Cada una declara su nombre, cuántos
+// argumentos recibe y cómo se llama, así la validación de la cantidad de argumentos
+// se hace en un único lugar (ver `call`).
+//
+// Para agregar una función, se la implementa en functions.rs y se agrega a BUILTINS.
+
+use crate::error::EvalError;
+use crate::functions;
+use crate::random::Rng;
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// Una función que recibe los argumentos ya evaluados y el generador de números
+/// aleatorios (que solo usa rand()).
+pub type BuiltinFn = fn(&[Value], &mut Rng) -> Result<Value, EvalError>;
+
+pub struct Builtin {
+    pub name: &'static str,
+    /// Cantidad mínima de argumentos.
+    pub min_args: usize,
+    /// Cantidad máxima de argumentos.
+    pub max_args: usize,
+    /// Se llama solo si la cantidad de argumentos está entre `min_args` y `max_args`.
+    pub call: BuiltinFn,
+}
+
+/// Funciones que reciben los booleanos tal cual. Las demás los reciben convertidos
+/// a 1 (verdadero) o 0 (falso), así no tienen que distinguirlos de los números.
+const KEEPS_BOOLEANS: &[&str] = &["class", "disp", "if"];
+
+/// Todas las funciones disponibles, en el orden en el que se muestran en la ayuda.
+pub const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "abs",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::abs(&args[0]),
+    },
+    Builtin {
+        name: "sqrt",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::sqrt(&args[0]),
+    },
+    Builtin {
+        name: "pow",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::pow(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "inv",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::inverse(&args[0]),
+    },
+    Builtin {
+        name: "factorial",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::factorial(&args[0]),
+    },
+    Builtin {
+        name: "sin",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::sin(&args[0]),
+    },
+    Builtin {
+        name: "cos",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::cos(&args[0]),
+    },
+    Builtin {
+        name: "tan",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::tan(&args[0]),
+    },
+    Builtin {
+        name: "log",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::log(&args[0]),
+    },
+    Builtin {
+        name: "transpose",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::transpose(&args[0]),
+    },
+    Builtin {
+        name: "det",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::det(&args[0]),
+    },
+    Builtin {
+        name: "linsolve",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::linsolve(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "eig",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::eig(&args[0]),
+    },
+    Builtin {
+        name: "lu",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::lu(&args[0]),
+    },
+    Builtin {
+        name: "norm",
+        min_args: 1,
+        max_args: 2,
+        call: |args, _| functions::norm(&args[0], args.get(1)),
+    },
+    Builtin {
+        name: "reshape",
+        min_args: 3,
+        max_args: 3,
+        call: |args, _| functions::reshape(&args[0], &args[1], &args[2]),
+    },
+    Builtin {
+        name: "kron",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::kron(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "adj",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::adj(&args[0]),
+    },
+    Builtin {
+        name: "minor",
+        min_args: 3,
+        max_args: 3,
+        call: |args, _| functions::minor(&args[0], &args[1], &args[2]),
+    },
+    Builtin {
+        name: "rand",
+        min_args: 0,
+        max_args: 2,
+        call: |args, rng| match args {
+            [] => Ok(Value::Scalar(rng.next_f64())),
+            [n] => functions::rand(rng, n, n),
+            [m, n, ..] => functions::rand(rng, m, n),
+        },
+    },
+    Builtin {
+        name: "cond",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::cond(&args[0]),
+    },
+    Builtin {
+        name: "pinv",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::pinv(&args[0]),
+    },
+    Builtin {
+        name: "size",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::size(&args[0]),
+    },
+    Builtin {
+        name: "numel",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::numel(&args[0]),
+    },
+    Builtin {
+        name: "length",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::length(&args[0]),
+    },
+    Builtin {
+        name: "isempty",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::isempty(&args[0]),
+    },
+    Builtin {
+        name: "isscalar",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::isscalar(&args[0]),
+    },
+    Builtin {
+        name: "isvector",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::isvector(&args[0]),
+    },
+    Builtin {
+        name: "expm",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::expm(&args[0]),
+    },
+    Builtin {
+        name: "nchoosek",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::nchoosek(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "cbrt",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::cbrt(&args[0]),
+    },
+    Builtin {
+        name: "nthroot",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::nthroot(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "sind",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::sind(&args[0]),
+    },
+    Builtin {
+        name: "cosd",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::cosd(&args[0]),
+    },
+    Builtin {
+        name: "tand",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::tand(&args[0]),
+    },
+    Builtin {
+        name: "isequal",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::isequal(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "repmat",
+        min_args: 3,
+        max_args: 3,
+        call: |args, _| functions::repmat(&args[0], &args[1], &args[2]),
+    },
+    Builtin {
+        name: "fliplr",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::fliplr(&args[0]),
+    },
+    Builtin {
+        name: "flipud",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::flipud(&args[0]),
+    },
+    Builtin {
+        name: "rot90",
+        min_args: 1,
+        max_args: 2,
+        call: |args, _| functions::rot90(&args[0], args.get(1).unwrap_or(&Value::Scalar(1.0))),
+    },
+    Builtin {
+        name: "tril",
+        min_args: 1,
+        max_args: 2,
+        call: |args, _| functions::tril(&args[0], args.get(1).unwrap_or(&Value::Scalar(0.0))),
+    },
+    Builtin {
+        name: "triu",
+        min_args: 1,
+        max_args: 2,
+        call: |args, _| functions::triu(&args[0], args.get(1).unwrap_or(&Value::Scalar(0.0))),
+    },
+    Builtin {
+        name: "issymmetric",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::issymmetric(&args[0]),
+    },
+    Builtin {
+        name: "linsolve_verbose",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::linsolve_verbose(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "num2str",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::num2str(&args[0]),
+    },
+    Builtin {
+        name: "mat2str",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::mat2str(&args[0]),
+    },
+    Builtin {
+        name: "disp",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::disp(&args[0]),
+    },
+    Builtin {
+        name: "class",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::class(&args[0]),
+    },
+    Builtin {
+        name: "if",
+        min_args: 3,
+        max_args: 3,
+        call: |args, _| functions::if_else(&args[0], &args[1], &args[2]),
+    },
+    Builtin {
+        name: "clamp",
+        min_args: 3,
+        max_args: 3,
+        call: |args, _| functions::clamp(&args[0], &args[1], &args[2]),
+    },
+    Builtin {
+        name: "hypot",
+        min_args: 2,
+        max_args: 2,
+        call: |args, _| functions::hypot(&args[0], &args[1]),
+    },
+    Builtin {
+        name: "deg2rad",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::deg2rad(&args[0]),
+    },
+    Builtin {
+        name: "rad2deg",
+        min_args: 1,
+        max_args: 1,
+        call: |args, _| functions::rad2deg(&args[0]),
+    },
+];
+
+lazy_static::lazy_static! {
+    /// Índice de BUILTINS por nombre, para no recorrer la lista en cada llamada.
+    static ref BY_NAME: HashMap<&'static str, &'static Builtin> =
+        BUILTINS.iter().map(|builtin| (builtin.name, builtin)).collect();
+}
+
+/// Busca una función por su nombre.
+pub fn get(name: &str) -> Option<&'static Builtin> {
+    BY_NAME.get(name).copied()
+}
+
+/// Llama a la función con los argumentos dados, verificando antes que reciba esa
+/// cantidad de argumentos.
+pub fn call(builtin: &Builtin, args: &[Value], rng: &mut Rng) -> Result<Value, EvalError> {
+    if args.len() < builtin.min_args || args.len() > builtin.max_args {
+        return Err(EvalError::WrongArguments(format!(
+            "La función {}() recibe {}",
+            builtin.name,
+            arity_description(builtin.min_args, builtin.max_args)
+        )));
+    }
+    let has_booleans = args.iter().any(|arg| matches!(arg, Value::Bool(_)));
+    if !has_booleans || KEEPS_BOOLEANS.contains(&builtin.name) {
+        return (builtin.call)(args, rng);
+    }
+    let args: Vec<Value> = args.iter().map(Value::numeric).collect();
+    (builtin.call)(&args, rng)
+}
+
+/// Describe en palabras cuántos argumentos recibe una función, como "un argumento",
+/// "uno o dos argumentos" o "hasta dos argumentos".
+pub fn arity_description(min: usize, max: usize) -> String {
+    let number = |n: usize| match n {
+        1 => "uno".to_string(),
+        2 => "dos".to_string(),
+        3 => "tres".to_string(),
+        n => n.to_string(),
+    };
+    match (min, max) {
+        (0, 0) => "ningún argumento".to_string(),
+        (1, 1) => "un argumento".to_string(),
+        (min, max) if min == max => format!("{} argumentos", number(min)),
+        (0, max) => format!("hasta {} argumentos", number(max)),
+        (min, max) if max == min + 1 => format!("{} o {} argumentos", number(min), number(max)),
+        (min, max) => format!("entre {} y {} argumentos", number(min), number(max)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Matrix;
+
+    /// Llama a la función `name` con los argumentos dados.
+    fn call_named(name: &str, args: &[Value]) -> Result<Value, EvalError> {
+        call(get(name).unwrap(), args, &mut Rng::new(1))
+    }
+
+    #[test]
+    fn booleans_are_passed_as_numbers() {
+        let mut rng = Rng::new(1);
+        let result = call(get("factorial").unwrap(), &[Value::Bool(true)], &mut rng);
+        assert!(matches!(result, Ok(Value::Scalar(x)) if x == 1.0));
+
+        // class() recibe el booleano tal cual.
+        let result = call(get("class").unwrap(), &[Value::Bool(true)], &mut rng);
+        assert!(matches!(result, Ok(Value::Text(name)) if name == "logical"));
+    }
+
+    #[test]
+    fn linsolve_takes_two_arguments() {
+        let a = Value::Matrix(Matrix::from_row_slice(2, 2, &[2.0, 1.0, 1.0, 3.0]).unwrap());
+        let b = Value::Matrix(Matrix::from_row_slice(2, 1, &[3.0, 5.0]).unwrap());
+        let Ok(Value::Matrix(x)) = call_named("linsolve", &[a.clone(), b]) else {
+            panic!("se esperaba una solución");
+        };
+        assert!((x.get(0, 0).unwrap() - 0.8).abs() < 1e-12);
+        assert!((x.get(1, 0).unwrap() - 1.4).abs() < 1e-12);
+
+        let error = call_named("linsolve", &[a]).err().unwrap();
+        assert_eq!(
+            error,
+            EvalError::WrongArguments("La función linsolve() recibe dos argumentos".to_string())
+        );
+    }
+
+    #[test]
+    fn every_builtin_is_callable() {
+        let mut rng = Rng::new(1);
+        // No hay nombres repetidos, así que get() encuentra a todas.
+        assert_eq!(BY_NAME.len(), BUILTINS.len());
+        for builtin in BUILTINS {
+            for count in builtin.min_args..=builtin.max_args {
+                let args = vec![Value::Scalar(1.0); count];
+                let result = call(builtin, &args, &mut rng);
+                assert!(
+                    !matches!(result, Err(EvalError::WrongArguments(_))),
+                    "{}() con {} argumentos",
+                    builtin.name,
+                    count
+                );
+            }
+            let too_many = vec![Value::Scalar(1.0); builtin.max_args + 1];
+            assert!(matches!(
+                call(builtin, &too_many, &mut rng),
+                Err(EvalError::WrongArguments(_))
+            ));
+        }
+    }
+}
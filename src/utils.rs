@@ -1,4 +1,4 @@
-use std::f64::MIN_POSITIVE;
+use std::cell::Cell;
 
 /// Dada la naturaleza de los puntos flotantes, esta función compara dos
 /// números flotantes para ver si son iguales.
@@ -12,28 +12,147 @@ pub fn nearly_equal(a: f64, b: f64) -> bool {
     if a == b {
         // shortcut, handles infinities
         true
-    } else if a == 0.0 || b == 0.0 || (abs_a + abs_b < MIN_POSITIVE) {
+    } else if a == 0.0 || b == 0.0 || (abs_a + abs_b < f64::MIN_POSITIVE) {
         // a or b is zero or both are extremely close to it
         // relative error is less meaningful here
-        diff < (EPSILON * MIN_POSITIVE)
+        diff < (EPSILON * f64::MIN_POSITIVE)
     } else {
         // use relative error
         diff / (abs_a + abs_b) < EPSILON
     }
 }
 
+/// Calcula la función gamma, Γ(x), que extiende el factorial: Γ(n + 1) = n!.
+/// Se usa la aproximación de Lanczos (g = 7), con unos 15 dígitos de precisión.
+/// Para x < 1/2 se usa la fórmula de reflexión Γ(x)Γ(1 - x) = π / sin(πx).
+/// Ver https://en.wikipedia.org/wiki/Lanczos_approximation
+pub fn gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        return std::f64::consts::PI / ((std::f64::consts::PI * x).sin() * gamma(1.0 - x));
+    }
+
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    (2.0 * std::f64::consts::PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * sum
+}
+
+/// Calcula la distancia de Levenshtein entre dos textos: la menor cantidad de
+/// inserciones, borrados o reemplazos de caracteres para pasar de uno al otro.
+/// Ver https://en.wikipedia.org/wiki/Levenshtein_distance
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // Solo se guarda la fila anterior de la tabla de distancias.
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// El programa usa un único hilo. Que el formato sea por hilo permite que las pruebas,
+// que corren en paralelo, lo cambien sin afectarse entre sí.
+thread_local! {
+    /// Indica si los números se muestran con todos sus dígitos (`format long`)
+    /// o con 4 decimales (`format short`, por defecto).
+    static LONG_FORMAT: Cell<bool> = const { Cell::new(false) };
+
+    /// Indica si las matrices se muestran entre corchetes y con separadores
+    /// (`format brackets`), o solo con las columnas alineadas (`format plain`, por defecto).
+    static BRACKET_FORMAT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Cambia el modo en que se muestran los números. Ver `format_float`.
+pub fn set_long_format(long: bool) {
+    LONG_FORMAT.set(long);
+}
+
+/// Cambia el modo en que se muestran las matrices. Ver matrix/display.rs
+pub fn set_bracket_format(brackets: bool) {
+    BRACKET_FORMAT.set(brackets);
+}
+
+/// Retorna si las matrices se muestran entre corchetes.
+pub fn bracket_format() -> bool {
+    BRACKET_FORMAT.get()
+}
+
 /// Formatea un número flotante para que se parezca a un entero si es
-/// posible.
+/// posible. Si no, se muestra con 4 decimales, o con 15 dígitos
+/// significativos si está activado `format long`.
 pub fn format_float(n: f64) -> String {
-    if nearly_equal(n, 0.0) {
+    if n.is_nan() {
+        "nan".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else if nearly_equal(n, 0.0) {
         // Previene el caso "-0"
-        return "0".to_string();
+        "0".to_string()
     } else {
         let rounded = n.round();
         if nearly_equal(n, rounded) {
             format!("{}", rounded)
+        } else if LONG_FORMAT.get() {
+            // Se calculan los decimales necesarios para tener 15 dígitos significativos.
+            let integer_digits = n.abs().log10().floor() as i32 + 1;
+            let decimals = (15 - integer_digits).max(0) as usize;
+            format!("{:.*}", decimals, n)
         } else {
             format!("{:.4}", n)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_and_short_formats() {
+        let third = 1.0 / 3.0;
+        assert_eq!(format_float(third), "0.3333");
+        assert_eq!(format_float(-1234.5678), "-1234.5678");
+
+        set_long_format(true);
+        assert_eq!(format_float(third), "0.333333333333333");
+        assert_eq!(format_float(-1234.5678), "-1234.56780000000");
+        // Los enteros se muestran igual en los dos formatos.
+        assert_eq!(format_float(2.0), "2");
+
+        set_long_format(false);
+        assert_eq!(format_float(third), "0.3333");
+    }
+
+    #[test]
+    fn formats_infinities_and_nan() {
+        assert_eq!(format_float(f64::INFINITY), "inf");
+        assert_eq!(format_float(f64::NEG_INFINITY), "-inf");
+        assert_eq!(format_float(f64::NAN), "nan");
+        assert_eq!(format_float(1.0 / 0.0), "inf");
+    }
+}
@@ -1,11 +1,68 @@
 use super::matrix::Matrix;
+use super::parser::AstNode;
 use super::utils::format_float;
+use std::collections::HashMap;
 use std::fmt;
 
 #[derive(Clone)]
 pub enum Value {
     Scalar(f64),
     Matrix(Matrix),
+    Text(String),
+    Bool(bool),
+    Function(Function),
+}
+
+/// Función anónima, como `@(x) x^2`.
+#[derive(Clone)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub body: AstNode,
+    /// Valores de las variables que usa el cuerpo, tomados al definir la función.
+    /// Así, cambiar esas variables después no cambia la función (como en MATLAB).
+    pub captured: HashMap<String, Value>,
+    /// Texto con el que se escribió la función, para mostrarla.
+    pub source: String,
+}
+
+impl Value {
+    /// Retorna el número real si el valor es un escalar, o `None` en otro caso.
+    pub fn as_scalar(&self) -> Option<f64> {
+        match self {
+            Value::Scalar(s) => Some(*s),
+            Value::Bool(b) => Some(f64::from(*b)),
+            _ => None,
+        }
+    }
+
+    /// Retorna el nombre del tipo del valor en plural, para los mensajes de error
+    /// (como "El seno no está definido para textos").
+    pub fn type_plural(&self) -> &'static str {
+        match self {
+            Value::Scalar(_) => "números reales",
+            Value::Matrix(_) => "matrices",
+            Value::Text(_) => "textos",
+            Value::Bool(_) => "booleanos",
+            Value::Function(_) => "funciones",
+        }
+    }
+
+    /// Retorna el valor con los booleanos convertidos a 1 (verdadero) o 0 (falso),
+    /// para poder operar con ellos como números.
+    pub fn numeric(&self) -> Value {
+        match self {
+            Value::Bool(b) => Value::Scalar(f64::from(*b)),
+            value => value.clone(),
+        }
+    }
+
+    /// Como `numeric`, pero consume el valor en vez de copiarlo.
+    pub fn into_numeric(self) -> Value {
+        match self {
+            Value::Bool(b) => Value::Scalar(f64::from(b)),
+            value => value,
+        }
+    }
 }
 
 impl fmt::Display for Value {
@@ -13,6 +70,18 @@ impl fmt::Display for Value {
         match self {
             Value::Scalar(s) => write!(f, "{}", format_float(*s)),
             Value::Matrix(m) => write!(f, "{}", m),
+            // Un texto de varias líneas se muestra en un bloque, como las matrices.
+            Value::Text(t) if t.contains('\n') => {
+                writeln!(f)?;
+                for line in t.lines() {
+                    write!(f, "\n   {}", line)?;
+                }
+                writeln!(f)
+            }
+            Value::Text(t) => write!(f, "{}", t),
+            Value::Bool(true) => write!(f, "verdadero"),
+            Value::Bool(false) => write!(f, "falso"),
+            Value::Function(function) => write!(f, "{}", function.source),
         }
     }
 }
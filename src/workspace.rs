@@ -0,0 +1,207 @@
+// En este archivo se implementa el guardado y la carga de variables en archivos
+// de texto (comandos `save` y `load`). Excede a la materia.
+//
+// El formato es de una variable por línea:
+//
+//   x scalar 5
+//   A matrix 2 2 1 2 3 4
+//   b bool true
+//   s text x1 = 2 - x2\nx2 = x2
+//
+// Las funciones anónimas, las constantes (pi, e, inf y nan) y ans no se guardan.
+//
+// Para las matrices se guardan las filas, las columnas y los elementos fila por fila.
+// Los números se escriben con `{}`, que genera la representación más corta que
+// se vuelve a leer exactamente igual. Los textos ocupan el resto de la línea, con
+// los saltos de línea y las barras invertidas escapados.
+
+use super::matrix::Matrix;
+use super::value::Value;
+use super::Variables;
+use std::fs;
+
+/// Primera línea de todo archivo guardado, para reconocer el formato.
+const HEADER: &str = "# matec workspace";
+
+/// Guarda todas las variables en el archivo `path`.
+pub fn save(variables: &Variables, path: &str) -> Result<(), String> {
+    let mut names: Vec<&String> = variables.keys().collect();
+    names.sort();
+
+    let mut lines = vec![HEADER.to_string()];
+    let mut skipped = Vec::new();
+    for name in names {
+        if super::is_reserved(name) {
+            continue;
+        }
+        let line = match &variables[name] {
+            Value::Scalar(s) => format!("{} scalar {}", name, s),
+            Value::Matrix(m) => {
+                let mut line = format!("{} matrix {} {}", name, m.rows(), m.cols());
+                for (_, _, val) in m {
+                    line.push_str(&format!(" {}", val));
+                }
+                line
+            }
+            Value::Bool(b) => format!("{} bool {}", name, b),
+            Value::Text(t) => format!(
+                "{} text {}",
+                name,
+                t.replace('\\', "\\\\").replace('\n', "\\n")
+            ),
+            // Las funciones anónimas dependen de los valores que capturaron, así que
+            // no se guardan.
+            Value::Function(_) => {
+                skipped.push(name.as_str());
+                continue;
+            }
+        };
+        lines.push(line);
+    }
+    if !skipped.is_empty() {
+        output!(
+            "Nota: las funciones anónimas no se guardan ({})",
+            skipped.join(", ")
+        );
+    }
+
+    fs::write(path, lines.join("\n") + "\n")
+        .map_err(|e| format!("No se pudo guardar el archivo \"{}\": {}", path, e))
+}
+
+/// Lee las variables guardadas en el archivo `path`.
+pub fn load(path: &str) -> Result<Variables, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("No se pudo abrir el archivo \"{}\": {}", path, e))?;
+
+    let mut lines = contents.lines().enumerate();
+    if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+        return Err(format!(
+            "El archivo \"{}\" no es un archivo de variables válido",
+            path
+        ));
+    }
+
+    let mut variables = Variables::new();
+    for (i, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (name, value) = parse_line(line).ok_or(format!(
+            "El archivo \"{}\" está mal formado (línea {})",
+            path,
+            i + 1
+        ))?;
+        variables.insert(name, value);
+    }
+    Ok(variables)
+}
+
+/// Interpreta una línea del archivo. Devuelve `None` si está mal formada.
+fn parse_line(line: &str) -> Option<(String, Value)> {
+    let mut parts = line.split_whitespace();
+    let name = parts.next()?.to_string();
+
+    // Los textos pueden contener espacios, así que se leen aparte.
+    if let Some(text) = line
+        .strip_prefix(name.as_str())
+        .and_then(|rest| rest.trim_start().strip_prefix("text "))
+    {
+        return Some((name, Value::Text(unescape(text)?)));
+    }
+
+    let value = match parts.next()? {
+        "scalar" => Value::Scalar(parts.next()?.parse().ok()?),
+        "bool" => Value::Bool(parts.next()?.parse().ok()?),
+        "matrix" => {
+            let rows: usize = parts.next()?.parse().ok()?;
+            let cols: usize = parts.next()?.parse().ok()?;
+            let data = (0..rows.checked_mul(cols)?)
+                .map(|_| parts.next()?.parse().ok())
+                .collect::<Option<Vec<f64>>>()?;
+            Value::Matrix(Matrix::from_row_slice(rows, cols, &data).ok()?)
+        }
+        _ => return None,
+    };
+
+    // No puede haber datos de más.
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((name, value))
+}
+
+/// Deshace el escapado de `save`. Devuelve `None` si hay una secuencia inválida.
+fn unescape(text: &str) -> Option<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                'n' => result.push('\n'),
+                '\\' => result.push('\\'),
+                _ => return None,
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ruta de un archivo temporal para la prueba `name`.
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("matec-{}-{}", name, std::process::id()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut variables = Variables::new();
+        variables.insert("x".to_string(), Value::Scalar(0.1 + 0.2));
+        variables.insert("n".to_string(), Value::Scalar(-5.0));
+        let a = Matrix::from_row_slice(2, 3, &[1.0, -2.5, 1.0 / 3.0, 4.0, 5e-20, 6.0]).unwrap();
+        variables.insert("A".to_string(), Value::Matrix(a.clone()));
+        variables.insert("s".to_string(), Value::Text("a b\\c\nd".to_string()));
+        variables.insert("pi".to_string(), Value::Scalar(3.0));
+
+        let path = temp_path("round-trip");
+        save(&variables, &path).unwrap();
+        let loaded = load(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.len(), 4);
+        assert!(matches!(loaded["x"], Value::Scalar(x) if x == 0.1 + 0.2));
+        assert!(matches!(loaded["n"], Value::Scalar(n) if n == -5.0));
+        assert!(matches!(&loaded["s"], Value::Text(s) if s == "a b\\c\nd"));
+        let Value::Matrix(loaded_a) = &loaded["A"] else {
+            panic!("se esperaba una matriz");
+        };
+        assert_eq!((loaded_a.rows(), loaded_a.cols()), (2, 3));
+        assert!((&a)
+            .into_iter()
+            .zip(loaded_a)
+            .all(|((_, _, x), (_, _, y))| x == y));
+    }
+
+    #[test]
+    fn load_reports_missing_and_malformed_files() {
+        let path = temp_path("malformed");
+        assert!(matches!(load(&path), Err(e) if e.starts_with("No se pudo abrir el archivo")));
+
+        for line in [
+            "A matrix 2 2 1 2 3",
+            "A matrix 9999999999999 9999999999999 1",
+        ] {
+            fs::write(&path, format!("{}\n{}\n", HEADER, line)).unwrap();
+            let result = load(&path);
+            fs::remove_file(&path).unwrap();
+            assert!(matches!(result, Err(e) if e.ends_with("está mal formado (línea 2)")));
+        }
+    }
+}
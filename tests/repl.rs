@@ -0,0 +1,114 @@
+// Pruebas del programa completo: se le pasa la entrada por stdin y se revisa lo
+// que muestra, como si lo usara una persona.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Ejecuta matec con `input` como entrada estándar y espera a que termine.
+fn run(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_matec"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn exits_cleanly_at_end_of_input() {
+    let output = run("");
+    assert!(output.status.success());
+
+    // También en medio de una matriz de varias líneas.
+    let output = run("x = 2\n[1, 2");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("x = 2"), "{}", stdout);
+}
+
+#[test]
+fn diary_records_commands_and_results() {
+    let path = std::env::temp_dir().join(format!("matec-diary-{}", std::process::id()));
+    let input = format!(
+        "diary \"{}\"\nx = 1 + 2\ny = x * 2\ndiary off\nz = 5\n",
+        path.display()
+    );
+    assert!(run(&input).status.success());
+
+    let diary = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(diary.contains("> x = 1 + 2\nx = 3"), "{}", diary);
+    assert!(diary.contains("> y = x * 2\ny = 6"), "{}", diary);
+    assert!(!diary.contains("z = 5"), "{}", diary);
+}
+
+#[test]
+fn linsolve_verbose_shows_the_rref() {
+    let output = run("linsolve_verbose([2, 1; 1, 3], [3; 5])\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("rref(A|b) = \n\n   1   0   0.8000\n   0   1   1.4000\n"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("El sistema es compatible determinado"));
+}
+
+#[test]
+fn an_error_does_not_stop_the_following_statements() {
+    let output = run("a = 1\na = sqrt(-1), b = 2 + 2\na\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("Error: No se puede calcular la raíz cuadrada"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("b = 4"), "{}", stdout);
+    // La variable conserva su valor anterior.
+    assert!(stdout.contains("> ans = 1"), "{}", stdout);
+}
+
+#[test]
+fn disp_prints_only_the_value() {
+    let output = run("disp(2.5)\ndisp([1, 2; 3, 4])\ndisp(\"hola\")\nans\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("> 2.5000\n>    1   2\n   3   4\n> hola\n"),
+        "{}",
+        stdout
+    );
+    // disp() no guarda su argumento en ans.
+    assert!(
+        stdout.contains("La variable \"ans\" no está definida"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn piped_input_keeps_the_prompt() {
+    // Sin una terminal no se usa rustyline, sino la entrada estándar directamente.
+    let output = run("x = 1 + 1\nx * 3\n");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("> x = 2\n> ans = 6\n> "), "{}", stdout);
+}
+
+#[test]
+fn assigning_to_a_constant_is_rejected() {
+    let output = run("pi = 3\npi\nx = 3\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("Error: No se puede asignar a \"pi\" porque es una constante"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("> ans = 3.1416\n> x = 3\n"), "{}", stdout);
+}